@@ -0,0 +1,439 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+extern crate aoc_parse;
+use aoc_parse::{CharGrid, ParseError};
+
+use crate::Point;
+
+/// a rectangular grid of cells indexed by `Point`, with (0, 0) in the top left
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// make a grid of the given size with every cell set to the same value
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Grid {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// make a grid from its rows, which must all be the same width
+    pub fn from_rows(rows: Vec<Vec<T>>) -> Result<Self, ParseError> {
+        let width = rows.first().map_or(0, Vec::len);
+        let height = rows.len();
+        let mut cells = Vec::with_capacity(width * height);
+        for (y, row) in rows.into_iter().enumerate() {
+            if row.len() != width {
+                return Err(ParseError::RaggedGrid(y, row.len(), width));
+            }
+            cells.extend(row);
+        }
+
+        Ok(Grid {
+            cells,
+            width,
+            height,
+        })
+    }
+
+    /// parse puzzle input into a grid, converting each character with the given function
+    pub fn parse(input: &str, mut f: impl FnMut(char) -> T) -> Result<Self, ParseError> {
+        Ok(Grid::from(aoc_parse::char_grid(input)?).map(|&c| f(c)))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// whether the point is inside the grid
+    pub fn in_bounds(&self, p: Point) -> bool {
+        p.x >= 0 && p.y >= 0 && (p.x as usize) < self.width && (p.y as usize) < self.height
+    }
+
+    /// the position of the point in the flattened cells, if it's in bounds
+    fn offset(&self, p: Point) -> Option<usize> {
+        if self.in_bounds(p) {
+            Some(p.y as usize * self.width + p.x as usize)
+        } else {
+            None
+        }
+    }
+
+    /// the point at a position in the flattened cells
+    fn point(&self, offset: usize) -> Point {
+        Point::new((offset % self.width) as i64, (offset / self.width) as i64)
+    }
+
+    pub fn get(&self, p: Point) -> Option<&T> {
+        self.offset(p).map(|i| &self.cells[i])
+    }
+
+    pub fn get_mut(&mut self, p: Point) -> Option<&mut T> {
+        self.offset(p).map(|i| &mut self.cells[i])
+    }
+
+    /// set the value at the point, returning the old value or None if the point is out of bounds
+    pub fn set(&mut self, p: Point, value: T) -> Option<T> {
+        self.get_mut(p).map(|cell| std::mem::replace(cell, value))
+    }
+
+    /// every point in the grid, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point> + '_ {
+        (0..self.cells.len()).map(|i| self.point(i))
+    }
+
+    /// every cell along with its point, row by row
+    pub fn iter(&self) -> impl Iterator<Item = (Point, &T)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(i, cell)| (self.point(i), cell))
+    }
+
+    /// every cell along with its point, row by row
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (Point, &mut T)> + '_ {
+        let width = self.width;
+        self.cells
+            .iter_mut()
+            .enumerate()
+            .map(move |(i, cell)| (Point::new((i % width) as i64, (i / width) as i64), cell))
+    }
+
+    /// the orthogonally adjacent points that are inside the grid
+    pub fn neighbors4(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        p.neighbors4().into_iter().filter(|&n| self.in_bounds(n))
+    }
+
+    /// the adjacent points including diagonals that are inside the grid
+    pub fn neighbors8(&self, p: Point) -> impl Iterator<Item = Point> + '_ {
+        p.neighbors8().into_iter().filter(|&n| self.in_bounds(n))
+    }
+
+    /// the cells in row y, from left to right (panics if y is out of bounds)
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(
+            y < self.height,
+            "row {y} is out of bounds for a grid of height {}",
+            self.height
+        );
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// every row, from top to bottom
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> + '_ {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    /// the cells in column x, from top to bottom (panics if x is out of bounds)
+    pub fn col(&self, x: usize) -> impl Iterator<Item = &T> + '_ {
+        assert!(
+            x < self.width,
+            "column {x} is out of bounds for a grid of width {}",
+            self.width
+        );
+        self.cells
+            .iter()
+            .skip(x)
+            .step_by(self.width.max(1))
+            .take(self.height)
+    }
+
+    /// every column, from left to right
+    pub fn cols(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        (0..self.width).map(|x| self.col(x))
+    }
+
+    /// the cells from the start point onwards in steps of delta until leaving the grid
+    /// e.g. a delta of (1, 1) walks down and to the right
+    pub fn ray(&self, start: Point, delta: Point) -> impl Iterator<Item = &T> + '_ {
+        std::iter::successors(Some(start), move |&p| Some(p + delta)).map_while(|p| self.get(p))
+    }
+
+    /// every diagonal running down and to the right, starting from the bottom left corner
+    pub fn diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        let starts = (0..self.height as i64)
+            .rev()
+            .map(|y| Point::new(0, y))
+            .chain((1..self.width as i64).map(|x| Point::new(x, 0)));
+        starts.map(|start| self.ray(start, Point::new(1, 1)))
+    }
+
+    /// every diagonal running down and to the left, starting from the top left corner
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = impl Iterator<Item = &T> + '_> + '_ {
+        let right = self.width as i64 - 1;
+        let starts = (0..self.width as i64)
+            .map(|x| Point::new(x, 0))
+            .chain((1..self.height as i64).map(move |y| Point::new(right, y)));
+        starts.map(|start| self.ray(start, Point::new(-1, 1)))
+    }
+
+    /// the first point (row by row) holding the value
+    pub fn find(&self, value: &T) -> Option<Point>
+    where
+        T: PartialEq,
+    {
+        self.position(|cell| cell == value)
+    }
+
+    /// every point holding the value, row by row
+    pub fn find_all<'a>(&'a self, value: &'a T) -> impl Iterator<Item = Point> + 'a
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(move |(_, cell)| *cell == value)
+            .map(|(p, _)| p)
+    }
+
+    /// the first point (row by row) whose value matches the predicate
+    pub fn position(&self, predicate: impl FnMut(&T) -> bool) -> Option<Point> {
+        self.cells.iter().position(predicate).map(|i| self.point(i))
+    }
+
+    /// make a new grid of the same size by converting every cell
+    pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(f).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// make a new grid with the size given by the function, filling each point from this grid
+    fn remap(&self, width: usize, height: usize, source: impl Fn(usize, usize) -> Point) -> Self
+    where
+        T: Clone,
+    {
+        let mut cells = Vec::with_capacity(width * height);
+        for y in 0..height {
+            for x in 0..width {
+                cells.push(self[source(x, y)].clone());
+            }
+        }
+
+        Grid {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// swap rows and columns (mirror along the main diagonal)
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.height, self.width, |x, y| {
+            Point::new(y as i64, x as i64)
+        })
+    }
+
+    /// rotate 90 degrees clockwise
+    pub fn rotate_right(&self) -> Self
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        self.remap(self.height, self.width, move |x, y| {
+            Point::new(y as i64, (height - 1 - x) as i64)
+        })
+    }
+
+    /// rotate 90 degrees counterclockwise
+    pub fn rotate_left(&self) -> Self
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        self.remap(self.height, self.width, move |x, y| {
+            Point::new((width - 1 - y) as i64, x as i64)
+        })
+    }
+
+    /// mirror left to right
+    pub fn flip_horizontal(&self) -> Self
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        self.remap(self.width, self.height, move |x, y| {
+            Point::new((width - 1 - x) as i64, y as i64)
+        })
+    }
+
+    /// mirror top to bottom
+    pub fn flip_vertical(&self) -> Self
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        self.remap(self.width, self.height, move |x, y| {
+            Point::new(x as i64, (height - 1 - y) as i64)
+        })
+    }
+}
+
+impl From<CharGrid> for Grid<char> {
+    fn from(grid: CharGrid) -> Self {
+        Grid {
+            cells: grid.cells.into_iter().flatten().collect(),
+            width: grid.width,
+            height: grid.height,
+        }
+    }
+}
+
+/// panics if the point is out of bounds, use `get` to check
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+    fn index(&self, p: Point) -> &T {
+        match self.offset(p) {
+            Some(i) => &self.cells[i],
+            None => panic!(
+                "point {p} is out of bounds for a {}x{} grid",
+                self.width, self.height
+            ),
+        }
+    }
+}
+
+/// panics if the point is out of bounds, use `get_mut` to check
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, p: Point) -> &mut T {
+        match self.offset(p) {
+            Some(i) => &mut self.cells[i],
+            None => panic!(
+                "point {p} is out of bounds for a {}x{} grid",
+                self.width, self.height
+            ),
+        }
+    }
+}
+
+/// print the grid one row per line with no separators, which is how the puzzles draw them
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a 3x2 grid:
+    /// abc
+    /// def
+    fn grid() -> Grid<char> {
+        Grid::parse("abc\ndef\n", |c| c).unwrap()
+    }
+
+    fn rows(grid: &Grid<char>) -> Vec<String> {
+        grid.rows().map(|row| row.iter().collect()).collect()
+    }
+
+    fn lines<'a, I: Iterator<Item = &'a char>>(lines: impl Iterator<Item = I>) -> Vec<String> {
+        lines.map(|line| line.collect()).collect()
+    }
+
+    #[test]
+    fn indexing() {
+        let mut grid = grid();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid[Point::new(2, 1)], 'f');
+        assert_eq!(grid.get(Point::new(3, 0)), None);
+        assert_eq!(grid.get(Point::new(0, -1)), None);
+        assert_eq!(grid.set(Point::new(1, 1), 'x'), Some('e'));
+        assert_eq!(grid.set(Point::new(1, 2), 'x'), None);
+        assert_eq!(grid.find(&'x'), Some(Point::new(1, 1)));
+        assert_eq!(grid.to_string(), "abc\ndxf\n");
+    }
+
+    #[test]
+    fn rows_and_cols() {
+        let grid = grid();
+        assert_eq!(grid.row(1), &['d', 'e', 'f']);
+        assert_eq!(lines(grid.cols()), vec!["ad", "be", "cf"]);
+        assert_eq!(grid.col(2).collect::<String>(), "cf");
+    }
+
+    #[test]
+    #[should_panic(expected = "column 4 is out of bounds")]
+    fn col_out_of_bounds() {
+        let _ = grid().col(4);
+    }
+
+    #[test]
+    #[should_panic(expected = "row 2 is out of bounds")]
+    fn row_out_of_bounds() {
+        grid().row(2);
+    }
+
+    #[test]
+    fn ragged_rows() {
+        assert_eq!(
+            Grid::from_rows(vec![vec![1, 2], vec![3]]),
+            Err(ParseError::RaggedGrid(1, 1, 2))
+        );
+    }
+
+    #[test]
+    fn transformations() {
+        let grid = grid();
+        assert_eq!(rows(&grid.transpose()), vec!["ad", "be", "cf"]);
+        assert_eq!(rows(&grid.rotate_right()), vec!["da", "eb", "fc"]);
+        assert_eq!(rows(&grid.rotate_left()), vec!["cf", "be", "ad"]);
+        assert_eq!(rows(&grid.flip_horizontal()), vec!["cba", "fed"]);
+        assert_eq!(rows(&grid.flip_vertical()), vec!["def", "abc"]);
+        assert_eq!(grid.rotate_right().rotate_left(), grid);
+        assert_eq!(
+            grid.rotate_right().rotate_right(),
+            grid.flip_horizontal().flip_vertical()
+        );
+    }
+
+    #[test]
+    fn diagonals() {
+        let grid = grid();
+        assert_eq!(lines(grid.diagonals()), vec!["d", "ae", "bf", "c"]);
+        assert_eq!(lines(grid.anti_diagonals()), vec!["a", "bd", "ce", "f"]);
+    }
+
+    #[test]
+    fn neighbors_at_the_edges() {
+        let grid = grid();
+        let corner: Vec<Point> = grid.neighbors4(Point::new(0, 0)).collect();
+        assert_eq!(corner, vec![Point::new(1, 0), Point::new(0, 1)]);
+        let edge: Vec<Point> = grid.neighbors4(Point::new(1, 1)).collect();
+        assert_eq!(
+            edge,
+            vec![Point::new(1, 0), Point::new(2, 1), Point::new(0, 1)]
+        );
+        let corner: Vec<Point> = grid.neighbors8(Point::new(2, 1)).collect();
+        assert_eq!(
+            corner,
+            vec![Point::new(2, 0), Point::new(1, 1), Point::new(1, 0)]
+        );
+        assert_eq!(grid.neighbors8(Point::new(1, 0)).count(), 5);
+    }
+}
@@ -0,0 +1,5 @@
+mod point;
+pub use point::{Dir, Point};
+
+mod grid;
+pub use grid::Grid;
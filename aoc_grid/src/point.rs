@@ -0,0 +1,177 @@
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// a position on a grid, with x increasing to the right and y increasing downwards (like the puzzle text)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Point { x, y }
+    }
+
+    /// the manhattan (taxicab) distance between two points
+    pub fn manhattan(self, other: Point) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// the point one step away in the given direction
+    pub fn step(self, dir: Dir) -> Point {
+        self + dir.delta()
+    }
+
+    /// the four orthogonally adjacent points, clockwise from up
+    pub fn neighbors4(self) -> [Point; 4] {
+        Dir::ALL.map(|dir| self.step(dir))
+    }
+
+    /// the eight adjacent points including diagonals, clockwise from up
+    pub fn neighbors8(self) -> [Point; 8] {
+        [
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ]
+        .map(|(dx, dy)| self + Point::new(dx, dy))
+    }
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl From<(i64, i64)> for Point {
+    fn from((x, y): (i64, i64)) -> Self {
+        Point { x, y }
+    }
+}
+
+impl Add for Point {
+    type Output = Point;
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Point) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Point;
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Point) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Point;
+    fn mul(self, scale: i64) -> Point {
+        Point::new(self.x * scale, self.y * scale)
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+    fn neg(self) -> Point {
+        Point::new(-self.x, -self.y)
+    }
+}
+
+impl Add<Dir> for Point {
+    type Output = Point;
+    fn add(self, dir: Dir) -> Point {
+        self.step(dir)
+    }
+}
+
+impl AddAssign<Dir> for Point {
+    fn add_assign(&mut self, dir: Dir) {
+        *self = self.step(dir);
+    }
+}
+
+/// one of the four orthogonal directions on a grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Dir {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Dir {
+    /// every direction, clockwise from up
+    pub const ALL: [Dir; 4] = [Dir::Up, Dir::Right, Dir::Down, Dir::Left];
+
+    /// the direction after turning 90 degrees counterclockwise
+    pub fn turn_left(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Left,
+            Dir::Right => Dir::Up,
+            Dir::Down => Dir::Right,
+            Dir::Left => Dir::Down,
+        }
+    }
+
+    /// the direction after turning 90 degrees clockwise
+    pub fn turn_right(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Right,
+            Dir::Right => Dir::Down,
+            Dir::Down => Dir::Left,
+            Dir::Left => Dir::Up,
+        }
+    }
+
+    /// the opposite direction
+    pub fn reverse(self) -> Dir {
+        match self {
+            Dir::Up => Dir::Down,
+            Dir::Right => Dir::Left,
+            Dir::Down => Dir::Up,
+            Dir::Left => Dir::Right,
+        }
+    }
+
+    /// the change in position from taking one step in this direction
+    pub fn delta(self) -> Point {
+        match self {
+            Dir::Up => Point::new(0, -1),
+            Dir::Right => Point::new(1, 0),
+            Dir::Down => Point::new(0, 1),
+            Dir::Left => Point::new(-1, 0),
+        }
+    }
+
+    /// parse a direction from the letters and arrows puzzles tend to use (U/R/D/L, N/E/S/W, ^/>/v/<)
+    pub fn from_char(c: char) -> Option<Dir> {
+        match c {
+            'U' | 'u' | 'N' | 'n' | '^' => Some(Dir::Up),
+            'R' | 'r' | 'E' | 'e' | '>' => Some(Dir::Right),
+            'D' | 'd' | 'S' | 's' | 'v' => Some(Dir::Down),
+            'L' | 'l' | 'W' | 'w' | '<' => Some(Dir::Left),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use std::fmt::Debug;
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+
+pub mod ranges;
+
+/// the signed integer types the number theory functions work with (i64 and i128)
+pub trait Integer:
+    Copy
+    + Ord
+    + Debug
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Rem<Output = Self>
+    + Neg<Output = Self>
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    fn checked_add(self, other: Self) -> Option<Self>;
+    fn checked_sub(self, other: Self) -> Option<Self>;
+    fn checked_mul(self, other: Self) -> Option<Self>;
+    fn checked_neg(self) -> Option<Self>;
+    fn rem_euclid(self, other: Self) -> Self;
+
+    /// (self * other) mod m without overflowing, for 0 <= self, other < m
+    fn mul_mod(self, other: Self, m: Self) -> Self;
+
+    /// a guess at the square root that's close enough to correct with a few steps
+    fn approx_sqrt(self) -> Self;
+}
+
+macro_rules! impl_integer {
+    ($t:ty) => {
+        const ZERO: Self = 0;
+        const ONE: Self = 1;
+
+        fn checked_add(self, other: Self) -> Option<Self> {
+            <$t>::checked_add(self, other)
+        }
+
+        fn checked_sub(self, other: Self) -> Option<Self> {
+            <$t>::checked_sub(self, other)
+        }
+
+        fn checked_mul(self, other: Self) -> Option<Self> {
+            <$t>::checked_mul(self, other)
+        }
+
+        fn checked_neg(self) -> Option<Self> {
+            <$t>::checked_neg(self)
+        }
+
+        fn rem_euclid(self, other: Self) -> Self {
+            <$t>::rem_euclid(self, other)
+        }
+
+        fn approx_sqrt(self) -> Self {
+            (self as f64).sqrt() as $t
+        }
+    };
+}
+
+impl Integer for i64 {
+    impl_integer!(i64);
+
+    fn mul_mod(self, other: Self, m: Self) -> Self {
+        // widening is enough for an i64
+        (self as i128 * other as i128).rem_euclid(m as i128) as i64
+    }
+}
+
+impl Integer for i128 {
+    impl_integer!(i128);
+
+    fn mul_mod(self, other: Self, m: Self) -> Self {
+        if let Some(product) = self.checked_mul(other) {
+            return product.rem_euclid(m);
+        }
+        // there's nothing wider to fall back on, so multiply by doubling and adding
+        let mut result = 0;
+        let mut a = self;
+        let mut b = other;
+        while b > 0 {
+            if b & 1 == 1 {
+                result = add_mod(result, a, m);
+            }
+            a = add_mod(a, a, m);
+            b >>= 1;
+        }
+        result
+    }
+}
+
+/// (a + b) mod m without overflowing, for 0 <= a, b < m
+fn add_mod<T: Integer>(a: T, b: T, m: T) -> T {
+    // a + b might not fit, but a - (m - b) always does
+    if a >= m - b {
+        a - (m - b)
+    } else {
+        a + b
+    }
+}
+
+/// the greatest common divisor, which is always non-negative (and gcd(0, 0) = 0)
+/// panics if it doesn't fit, which only happens for gcd(MIN, 0) and gcd(MIN, MIN), see `checked_gcd`
+pub fn gcd<T: Integer>(a: T, b: T) -> T {
+    checked_gcd(a, b).expect("gcd doesn't fit")
+}
+
+/// the greatest common divisor, or None if it doesn't fit (for gcd(MIN, 0) and gcd(MIN, MIN))
+pub fn checked_gcd<T: Integer>(a: T, b: T) -> Option<T> {
+    // work with negative numbers, since every positive number can be negated but MIN can't
+    let negative = |n: T| if n > T::ZERO { -n } else { n };
+    let (mut a, mut b) = (negative(a), negative(b));
+    while b != T::ZERO {
+        // MIN % -1 overflows even though the remainder is 0
+        let r = if b == -T::ONE { T::ZERO } else { a % b };
+        (a, b) = (b, r);
+    }
+    a.checked_neg()
+}
+
+/// the least common multiple, which is always non-negative (and 0 if either is 0)
+/// overflows like normal arithmetic (or panics in gcd), see `checked_lcm`
+pub fn lcm<T: Integer>(a: T, b: T) -> T {
+    if a == T::ZERO || b == T::ZERO {
+        return T::ZERO;
+    }
+    let l = a / gcd(a, b) * b;
+    if l < T::ZERO {
+        -l
+    } else {
+        l
+    }
+}
+
+/// the least common multiple, or None if it doesn't fit
+pub fn checked_lcm<T: Integer>(a: T, b: T) -> Option<T> {
+    if a == T::ZERO || b == T::ZERO {
+        return Some(T::ZERO);
+    }
+    let l = (a / checked_gcd(a, b)?).checked_mul(b)?;
+    if l < T::ZERO {
+        l.checked_neg()
+    } else {
+        Some(l)
+    }
+}
+
+/// the least common multiple of every number (1 if there aren't any), e.g. when several things
+/// with different periods all line up
+/// overflows like normal arithmetic, see `checked_lcm_all`
+pub fn lcm_all<T: Integer>(numbers: impl IntoIterator<Item = T>) -> T {
+    numbers.into_iter().fold(T::ONE, lcm)
+}
+
+/// the least common multiple of every number (1 if there aren't any), or None if it doesn't fit
+pub fn checked_lcm_all<T: Integer>(numbers: impl IntoIterator<Item = T>) -> Option<T> {
+    numbers
+        .into_iter()
+        .try_fold(T::ONE, |acc, n| checked_lcm(acc, n))
+}
+
+/// the extended euclidean algorithm: returns (g, x, y) where g = gcd(a, b) and a * x + b * y = g
+/// a and b shouldn't be MIN, since the quotients along the way or the gcd itself might not fit
+pub fn extended_gcd<T: Integer>(a: T, b: T) -> (T, T, T) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_x, mut x) = (T::ONE, T::ZERO);
+    let (mut old_y, mut y) = (T::ZERO, T::ONE);
+    while r != T::ZERO {
+        let q = old_r / r;
+        (old_r, r) = (r, old_r - q * r);
+        (old_x, x) = (x, old_x - q * x);
+        (old_y, y) = (y, old_y - q * y);
+    }
+    if old_r < T::ZERO {
+        (-old_r, -old_x, -old_y)
+    } else {
+        (old_r, old_x, old_y)
+    }
+}
+
+/// the x in 0..m with a * x = 1 (mod m), or None if a and m aren't coprime or m isn't positive
+pub fn mod_inverse<T: Integer>(a: T, m: T) -> Option<T> {
+    if m <= T::ZERO {
+        return None;
+    }
+    let (g, x, _) = extended_gcd(a.rem_euclid(m), m);
+    if g == T::ONE {
+        Some(x.rem_euclid(m))
+    } else {
+        None
+    }
+}
+
+/// base^exp mod m by repeated squaring, for a positive m
+pub fn mod_pow<T: Integer>(base: T, exp: u64, m: T) -> T {
+    let mut result = T::ONE.rem_euclid(m);
+    let mut base = base.rem_euclid(m);
+    let mut exp = exp;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result.mul_mod(base, m);
+        }
+        base = base.mul_mod(base, m);
+        exp >>= 1;
+    }
+    result
+}
+
+/// the chinese remainder theorem: given (residue, modulus) pairs, find the x with x = residue (mod modulus)
+/// for every pair. the moduli must be positive but don't have to be coprime
+/// returns (x, lcm of the moduli) with x in 0..lcm, or None if there's no solution
+/// overflows like normal arithmetic if the lcm of the moduli doesn't fit, see `checked_crt`
+pub fn crt<T: Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut x = T::ZERO;
+    let mut m = T::ONE;
+    for &(residue, modulus) in congruences {
+        (x, m) = merge_congruences(x, m, residue, modulus, |a, b| Some(a * b))?;
+    }
+    Some((x, m))
+}
+
+/// the chinese remainder theorem, returning None if there's no solution or the lcm of the moduli doesn't fit
+pub fn checked_crt<T: Integer>(congruences: &[(T, T)]) -> Option<(T, T)> {
+    let mut x = T::ZERO;
+    let mut m = T::ONE;
+    for &(residue, modulus) in congruences {
+        (x, m) = merge_congruences(x, m, residue, modulus, T::checked_mul)?;
+    }
+    Some((x, m))
+}
+
+/// combine x = r1 (mod m1) and x = r2 (mod m2) into a single congruence, using the given multiplication
+/// for the products that can overflow
+fn merge_congruences<T: Integer>(
+    r1: T,
+    m1: T,
+    r2: T,
+    m2: T,
+    mul: impl Fn(T, T) -> Option<T>,
+) -> Option<(T, T)> {
+    if m2 <= T::ZERO {
+        return None;
+    }
+    let r2 = r2.rem_euclid(m2);
+    let g = gcd(m1, m2);
+    let diff = r2.checked_sub(r1)?;
+    if diff % g != T::ZERO {
+        return None;
+    }
+
+    // x = r1 + m1 * k where k = (diff / g) * inverse(m1 / g) mod (m2 / g)
+    let reduced = m2 / g;
+    let inverse = mod_inverse(m1 / g, reduced)?;
+    let k = (diff / g).rem_euclid(reduced).mul_mod(inverse, reduced);
+    let lcm = mul(m1 / g, m2)?;
+    // m1 * k < lcm, so this only overflows if the lcm does
+    let x = r1.checked_add(mul(m1, k)?)?;
+
+    Some((x.rem_euclid(lcm), lcm))
+}
+
+/// the floor of the square root of n
+/// panics if n is negative, see `checked_isqrt`
+pub fn isqrt<T: Integer>(n: T) -> T {
+    checked_isqrt(n).expect("square root of a negative number")
+}
+
+/// the floor of the square root of n, or None if n is negative
+pub fn checked_isqrt<T: Integer>(n: T) -> Option<T> {
+    if n < T::ZERO {
+        return None;
+    }
+    // the floating point guess can be off by a little for big numbers, so nudge it into place
+    let mut root = n.approx_sqrt().max(T::ZERO);
+    while root.checked_mul(root).is_none_or(|square| square > n) {
+        root = root - T::ONE;
+    }
+    while (root + T::ONE)
+        .checked_mul(root + T::ONE)
+        .is_some_and(|square| square <= n)
+    {
+        root = root + T::ONE;
+    }
+    Some(root)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(gcd(12i64, 18), 6);
+        assert_eq!(gcd(-12i64, 18), 6);
+        assert_eq!(gcd(12i64, -18), 6);
+        assert_eq!(gcd(0i64, -5), 5);
+        assert_eq!(gcd(0i64, 0), 0);
+        assert_eq!(lcm(4i64, -6), 12);
+        assert_eq!(lcm(0i64, 6), 0);
+        assert_eq!(lcm_all([2i64, 3, 4, 5]), 60);
+        assert_eq!(lcm_all(Vec::<i64>::new()), 1);
+    }
+
+    #[test]
+    fn gcd_at_the_limits() {
+        assert_eq!(gcd(i64::MIN, 6), 2);
+        assert_eq!(gcd(i64::MIN, -1), 1);
+        assert_eq!(gcd(i64::MIN, i64::MIN / 2), 1 << 62);
+        assert_eq!(gcd(i64::MAX, i64::MIN), 1);
+        assert_eq!(gcd(i128::MIN, 3), 1);
+        assert_eq!(checked_gcd(i64::MIN, 0), None);
+        assert_eq!(checked_gcd(i64::MIN, i64::MIN), None);
+        assert_eq!(checked_gcd(i128::MIN, 0), None);
+        assert_eq!(checked_lcm(i64::MIN, 2), None);
+        assert_eq!(checked_lcm(i64::MIN, 0), Some(0));
+        assert_eq!(checked_lcm(i64::MAX, i64::MAX - 1), None);
+        assert_eq!(checked_lcm_all([1i64 << 40, 3 << 30, 5]), Some(15 << 40));
+    }
+
+    #[test]
+    #[should_panic(expected = "gcd doesn't fit")]
+    fn gcd_too_big() {
+        gcd(0, i64::MIN);
+    }
+
+    #[test]
+    fn extended_gcd_bezout() {
+        for a in -30i64..=30 {
+            for b in -30i64..=30 {
+                let (g, x, y) = extended_gcd(a, b);
+                assert_eq!(g, gcd(a, b), "gcd({a}, {b})");
+                assert_eq!(a * x + b * y, g, "bezout for ({a}, {b})");
+            }
+        }
+        let (a, b) = (i128::MAX, 1i128 << 100);
+        let (g, x, y) = extended_gcd(a, b);
+        assert_eq!(g, 1);
+        assert_eq!(a.wrapping_mul(x).wrapping_add(b.wrapping_mul(y)), 1);
+    }
+
+    #[test]
+    fn mod_inverse_round_trip() {
+        for m in 1i64..=50 {
+            for a in -60..=60 {
+                match mod_inverse(a, m) {
+                    Some(inverse) => {
+                        assert!((0..m).contains(&inverse));
+                        assert_eq!((a * inverse).rem_euclid(m), 1 % m, "{a} mod {m}");
+                    }
+                    None => assert_ne!(gcd(a, m), 1, "{a} mod {m}"),
+                }
+            }
+        }
+        let m = i64::MAX;
+        let inverse = mod_inverse(2, m).unwrap();
+        assert_eq!(2.mul_mod(inverse, m), 1);
+        assert_eq!(mod_inverse(3i64, 0), None);
+        assert_eq!(mod_inverse(3i64, -7), None);
+    }
+
+    #[test]
+    fn mod_pow_large() {
+        assert_eq!(mod_pow(2i64, 10, 1000), 24);
+        assert_eq!(mod_pow(3i64, 0, 1), 0);
+        // 2^127 - 1 is prime, and squaring anything near it needs mul_mod's fallback
+        let p = i128::MAX;
+        assert_eq!(mod_pow(2i128, 127, p), 1);
+        assert_eq!(mod_pow(2i128, 126, p), 1 << 126);
+        assert_eq!(mod_pow(p - 1, 2, p), 1);
+        assert_eq!(mod_pow(p - 2, 3, p), p - 8);
+        assert_eq!(mod_pow(3i128, 0, p), 1);
+        assert_eq!(mod_pow(-1i128, 3, p), p - 1);
+    }
+
+    #[test]
+    fn crt_coprime() {
+        assert_eq!(crt(&[(0i64, 3), (3, 4), (4, 5)]), Some((39, 60)));
+        assert_eq!(crt(&[(-1i64, 7), (15, 13)]), Some((41, 91)));
+        assert_eq!(crt::<i64>(&[]), Some((0, 1)));
+    }
+
+    #[test]
+    fn crt_not_coprime() {
+        // 14 is 2 mod 6 and 5 mod 9, and the solution repeats every lcm(6, 9) = 18
+        assert_eq!(crt(&[(2i64, 6), (5, 9)]), Some((14, 18)));
+        assert_eq!(checked_crt(&[(2i64, 6), (5, 9)]), Some((14, 18)));
+        assert_eq!(crt(&[(3i64, 4), (1, 6), (7, 10)]), Some((7, 60)));
+        // 1 mod 6 is odd but 2 mod 4 is even
+        assert_eq!(crt(&[(1i64, 6), (2, 4)]), None);
+        assert_eq!(checked_crt(&[(1i64, 6), (2, 9)]), None);
+        assert_eq!(crt(&[(1i64, 6), (1, 0)]), None);
+    }
+
+    #[test]
+    fn checked_crt_overflow() {
+        // the two primes multiply to more than an i64 can hold, but fit in an i128
+        let (p, q) = (4_294_967_311i64, 4_294_967_357i64);
+        assert_eq!(checked_crt(&[(1, p), (2, q)]), None);
+        let (x, m) = checked_crt(&[(1, p as i128), (2, q as i128)]).unwrap();
+        assert_eq!(m, p as i128 * q as i128);
+        assert_eq!((x % p as i128, x % q as i128), (1, 2));
+    }
+
+    #[test]
+    fn isqrt_bounds() {
+        assert_eq!(isqrt(0i64), 0);
+        assert_eq!(isqrt(15i64), 3);
+        assert_eq!(isqrt(16i64), 4);
+        let root = 3_037_000_499i64;
+        assert_eq!(isqrt(i64::MAX), root);
+        assert_eq!(isqrt(root * root), root);
+        assert_eq!(isqrt(root * root - 1), root - 1);
+        let root = 13_043_817_825_332_782_212i128;
+        assert_eq!(isqrt(i128::MAX), root);
+        assert_eq!(isqrt(root * root), root);
+        assert_eq!(isqrt(root * root - 1), root - 1);
+        assert_eq!(isqrt((1i128 << 126) - 1), (1 << 63) - 1);
+        assert_eq!(checked_isqrt(-1i64), None);
+        assert_eq!(checked_isqrt(i128::MIN), None);
+    }
+}
@@ -0,0 +1,446 @@
+use std::ops::{Bound, RangeBounds, RangeInclusive};
+
+/// convert any kind of integer range into an inclusive one, or None if it's empty
+/// inclusive ends are used so that every range, including ones ending at i64::MAX, can be represented
+fn inclusive(range: impl RangeBounds<i64>) -> Option<RangeInclusive<i64>> {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s.checked_add(1)?,
+        Bound::Unbounded => i64::MIN,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e,
+        Bound::Excluded(&e) => e.checked_sub(1)?,
+        Bound::Unbounded => i64::MAX,
+    };
+    (start <= end).then_some(start..=end)
+}
+
+/// a set of integers stored as sorted, disjoint, non-adjacent inclusive ranges
+/// any kind of range can be added or removed, e.g. `set.insert(3..5)` or `set.remove(10..)`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct RangeSet {
+    ranges: Vec<RangeInclusive<i64>>,
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        RangeSet { ranges: Vec::new() }
+    }
+
+    /// the ranges making up the set, in order
+    pub fn ranges(&self) -> &[RangeInclusive<i64>] {
+        &self.ranges
+    }
+
+    /// the number of integers in the set, which is a u128 because `..` holds 2^64 of them
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|r| r.end().abs_diff(*r.start()) as u128 + 1)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        // the last range starting at or before n is the only one that could contain it
+        let i = self.ranges.partition_point(|r| *r.start() <= n);
+        i > 0 && n <= *self.ranges[i - 1].end()
+    }
+
+    /// the smallest integer in the set
+    pub fn min(&self) -> Option<i64> {
+        self.ranges.first().map(|r| *r.start())
+    }
+
+    /// the largest integer in the set
+    pub fn max(&self) -> Option<i64> {
+        self.ranges.last().map(|r| *r.end())
+    }
+
+    /// add every integer in the range to the set, merging it with any ranges it overlaps or touches
+    pub fn insert(&mut self, range: impl RangeBounds<i64>) {
+        let Some(new) = inclusive(range) else {
+            return;
+        };
+        // the ranges from first to last (exclusive) overlap or touch the new one
+        let first = self
+            .ranges
+            .partition_point(|r| r.end().saturating_add(1) < *new.start());
+        let last = self
+            .ranges
+            .partition_point(|r| *r.start() <= new.end().saturating_add(1));
+        let (mut start, mut end) = new.into_inner();
+        if first < last {
+            start = start.min(*self.ranges[first].start());
+            end = end.max(*self.ranges[last - 1].end());
+        }
+        self.ranges.splice(first..last, [start..=end]);
+    }
+
+    /// remove every integer in the range from the set, splitting any range it cuts through
+    pub fn remove(&mut self, range: impl RangeBounds<i64>) {
+        let Some(cut) = inclusive(range) else {
+            return;
+        };
+        // the ranges from first to last (exclusive) overlap the cut
+        let first = self.ranges.partition_point(|r| r.end() < cut.start());
+        let last = self.ranges.partition_point(|r| r.start() <= cut.end());
+        if first >= last {
+            return;
+        }
+        // neither end of the cut can be at the limit of i64 if there's something left beyond it
+        let mut leftovers = Vec::with_capacity(2);
+        if self.ranges[first].start() < cut.start() {
+            leftovers.push(*self.ranges[first].start()..=cut.start() - 1);
+        }
+        if self.ranges[last - 1].end() > cut.end() {
+            leftovers.push(cut.end() + 1..=*self.ranges[last - 1].end());
+        }
+        self.ranges.splice(first..last, leftovers);
+    }
+
+    /// the integers in either set
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut result = self.clone();
+        for r in &other.ranges {
+            result.insert(r.clone());
+        }
+        result
+    }
+
+    /// the integers in both sets
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        // walk both lists of ranges at once, keeping the overlap of each pair that overlaps
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let overlap = *a.start().max(b.start())..=*a.end().min(b.end());
+            if !overlap.is_empty() {
+                ranges.push(overlap);
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    /// the integers in this set but not the other one
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut result = self.clone();
+        for r in &other.ranges {
+            result.remove(r.clone());
+        }
+        result
+    }
+
+    /// every integer in the set, in order
+    pub fn iter(&self) -> impl Iterator<Item = i64> + '_ {
+        self.ranges.iter().flat_map(RangeInclusive::clone)
+    }
+}
+
+impl<R: RangeBounds<i64>> FromIterator<R> for RangeSet {
+    /// merge any number of ranges into a set
+    fn from_iter<I: IntoIterator<Item = R>>(iter: I) -> Self {
+        let mut set = RangeSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+/// an axis aligned box in N dimensions covering the integer points from min (inclusive) to max (exclusive)
+/// on every axis, e.g. a `Cuboid<3>` for the reactor cubes in 2021 day 22
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid<const N: usize> {
+    pub min: [i64; N],
+    pub max: [i64; N],
+}
+
+impl<const N: usize> Cuboid<N> {
+    pub fn new(min: [i64; N], max: [i64; N]) -> Self {
+        Cuboid { min, max }
+    }
+
+    /// the cuboid from min to max with both ends included, which is how the puzzles usually describe them
+    /// panics if max is i64::MAX on any axis, since the exclusive max of that edge can't be represented
+    pub fn from_inclusive(min: [i64; N], max: [i64; N]) -> Self {
+        Cuboid {
+            min,
+            max: max.map(|m| {
+                m.checked_add(1)
+                    .expect("a cuboid can't include points at i64::MAX")
+            }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        (0..N).any(|axis| self.min[axis] >= self.max[axis])
+    }
+
+    /// the number of integer points in the cuboid
+    pub fn volume(&self) -> u128 {
+        if self.is_empty() {
+            return 0;
+        }
+        (0..N)
+            .map(|axis| self.max[axis].abs_diff(self.min[axis]) as u128)
+            .product()
+    }
+
+    pub fn contains(&self, point: [i64; N]) -> bool {
+        (0..N).all(|axis| self.min[axis] <= point[axis] && point[axis] < self.max[axis])
+    }
+
+    pub fn intersects(&self, other: &Cuboid<N>) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// the cuboid covered by both, if they overlap
+    pub fn intersection(&self, other: &Cuboid<N>) -> Option<Cuboid<N>> {
+        let overlap = Cuboid {
+            min: std::array::from_fn(|axis| self.min[axis].max(other.min[axis])),
+            max: std::array::from_fn(|axis| self.max[axis].min(other.max[axis])),
+        };
+        if overlap.is_empty() {
+            None
+        } else {
+            Some(overlap)
+        }
+    }
+
+    /// the parts of this cuboid that aren't covered by the other one, as at most 2N disjoint cuboids
+    pub fn subtract(&self, other: &Cuboid<N>) -> Vec<Cuboid<N>> {
+        let Some(overlap) = self.intersection(other) else {
+            return if self.is_empty() { vec![] } else { vec![*self] };
+        };
+
+        // slice off the parts below and above the overlap one axis at a time, shrinking what's left each time
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        for axis in 0..N {
+            if rest.min[axis] < overlap.min[axis] {
+                let mut below = rest;
+                below.max[axis] = overlap.min[axis];
+                pieces.push(below);
+                rest.min[axis] = overlap.min[axis];
+            }
+            if rest.max[axis] > overlap.max[axis] {
+                let mut above = rest;
+                above.min[axis] = overlap.max[axis];
+                pieces.push(above);
+                rest.max[axis] = overlap.max[axis];
+            }
+        }
+        pieces
+    }
+}
+
+/// a set of integer points in N dimensions stored as disjoint cuboids
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CuboidSet<const N: usize> {
+    cuboids: Vec<Cuboid<N>>,
+}
+
+impl<const N: usize> Default for CuboidSet<N> {
+    fn default() -> Self {
+        CuboidSet::new()
+    }
+}
+
+impl<const N: usize> CuboidSet<N> {
+    pub fn new() -> Self {
+        CuboidSet {
+            cuboids: Vec::new(),
+        }
+    }
+
+    /// the disjoint cuboids making up the set, in no particular order
+    pub fn cuboids(&self) -> &[Cuboid<N>] {
+        &self.cuboids
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cuboids.is_empty()
+    }
+
+    /// the number of integer points in the set
+    pub fn volume(&self) -> u128 {
+        self.cuboids.iter().map(Cuboid::volume).sum()
+    }
+
+    pub fn contains(&self, point: [i64; N]) -> bool {
+        self.cuboids.iter().any(|c| c.contains(point))
+    }
+
+    /// add every point in the cuboid to the set
+    pub fn insert(&mut self, cuboid: Cuboid<N>) {
+        self.remove(&cuboid);
+        if !cuboid.is_empty() {
+            self.cuboids.push(cuboid);
+        }
+    }
+
+    /// remove every point in the cuboid from the set
+    pub fn remove(&mut self, cuboid: &Cuboid<N>) {
+        self.cuboids = self
+            .cuboids
+            .iter()
+            .flat_map(|c| c.subtract(cuboid))
+            .collect();
+    }
+
+    /// the points in both this set and the cuboid
+    pub fn intersection(&self, cuboid: &Cuboid<N>) -> CuboidSet<N> {
+        CuboidSet {
+            cuboids: self
+                .cuboids
+                .iter()
+                .filter_map(|c| c.intersection(cuboid))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // a list holding a single range is what these tests mean
+    #![allow(clippy::single_range_in_vec_init)]
+
+    use super::*;
+
+    #[test]
+    fn insert_merges() {
+        let mut set = RangeSet::new();
+        set.insert(1..3);
+        set.insert(10..12);
+        assert_eq!(set.ranges(), &[1..=2, 10..=11]);
+        // touching half open ranges
+        set.insert(3..5);
+        assert_eq!(set.ranges(), &[1..=4, 10..=11]);
+        // adjacent inclusive ranges
+        set.insert(12..=13);
+        assert_eq!(set.ranges(), &[1..=4, 10..=13]);
+        // nested inside an existing range
+        set.insert(2..=3);
+        assert_eq!(set.ranges(), &[1..=4, 10..=13]);
+        // swallowing several ranges
+        set.insert(0..20);
+        assert_eq!(set.ranges(), &[0..=19]);
+        set.insert(7..7);
+        assert_eq!(set.ranges(), &[0..=19]);
+        assert_eq!(set.len(), 20);
+        assert_eq!((set.min(), set.max()), (Some(0), Some(19)));
+    }
+
+    #[test]
+    fn remove_splits() {
+        let mut set: RangeSet = [0..10, 20..30, 40..50].into_iter().collect();
+        // from the middle of one range
+        set.remove(3..=4);
+        assert_eq!(set.ranges(), &[0..=2, 5..=9, 20..=29, 40..=49]);
+        // across several ranges, leaving both ends
+        set.remove(8..45);
+        assert_eq!(set.ranges(), &[0..=2, 5..=7, 45..=49]);
+        // exactly one range, and a gap that's already empty
+        set.remove(5..8);
+        set.remove(10..20);
+        assert_eq!(set.ranges(), &[0..=2, 45..=49]);
+        set.remove(..=0);
+        set.remove(48..);
+        assert_eq!(set.ranges(), &[1..=2, 45..=47]);
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![1, 2, 45, 46, 47]);
+        assert!(set.contains(45) && !set.contains(3) && !set.contains(0));
+        set.remove(..);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn set_operations() {
+        let a: RangeSet = [0..10, 20..30].into_iter().collect();
+        let b: RangeSet = [5..25, 28..29].into_iter().collect();
+        assert_eq!(a.union(&b).ranges(), &[0..=29]);
+        assert_eq!(a.intersection(&b).ranges(), &[5..=9, 20..=24, 28..=28]);
+        assert_eq!(a.difference(&b).ranges(), &[0..=4, 25..=27, 29..=29]);
+        assert_eq!(b.difference(&a).ranges(), &[10..=19]);
+    }
+
+    #[test]
+    fn extreme_bounds() {
+        let mut set = RangeSet::new();
+        set.insert(i64::MAX - 1..=i64::MAX);
+        assert_eq!(set.ranges(), &[i64::MAX - 1..=i64::MAX]);
+        assert!(set.contains(i64::MAX));
+        assert_eq!(set.iter().collect::<Vec<_>>(), vec![i64::MAX - 1, i64::MAX]);
+        set.insert(..);
+        assert_eq!(set.len(), 1 << 64);
+        assert_eq!((set.min(), set.max()), (Some(i64::MIN), Some(i64::MAX)));
+        set.remove((Bound::Excluded(i64::MIN), Bound::Unbounded));
+        assert_eq!(set.ranges(), &[i64::MIN..=i64::MIN]);
+        set.insert(i64::MAX..);
+        set.remove(..i64::MAX);
+        assert_eq!(set.ranges(), &[i64::MAX..=i64::MAX]);
+        // ranges that are empty because of where they end
+        set.insert((Bound::Excluded(i64::MAX), Bound::Unbounded));
+        set.insert(..i64::MIN);
+        assert_eq!(set.len(), 1);
+
+        let cuboid = Cuboid::from_inclusive([0, i64::MIN], [i64::MAX - 1, i64::MIN]);
+        assert_eq!(cuboid.max, [i64::MAX, i64::MIN + 1]);
+        assert_eq!(cuboid.volume(), i64::MAX as u128);
+    }
+
+    #[test]
+    #[should_panic(expected = "i64::MAX")]
+    fn cuboid_past_max() {
+        Cuboid::from_inclusive([0], [i64::MAX]);
+    }
+
+    #[test]
+    fn cuboid_subtract() {
+        let cube = Cuboid::from_inclusive([0, 0, 0], [2, 2, 2]);
+        let middle = Cuboid::from_inclusive([1, 1, 1], [1, 1, 1]);
+        let pieces = cube.subtract(&middle);
+        assert_eq!(pieces.len(), 6);
+        assert_eq!(pieces.iter().map(Cuboid::volume).sum::<u128>(), 26);
+        for (i, a) in pieces.iter().enumerate() {
+            assert!(!a.contains([1, 1, 1]));
+            assert!(pieces[i + 1..].iter().all(|b| !a.intersects(b)));
+        }
+
+        // a corner only needs one cut per axis
+        let corner = Cuboid::new([2, 2, 2], [5, 5, 5]);
+        assert_eq!(cube.subtract(&corner).len(), 3);
+        assert_eq!(
+            cube.subtract(&Cuboid::new([3, 0, 0], [4, 3, 3])),
+            vec![cube]
+        );
+        assert_eq!(cube.subtract(&cube), vec![]);
+    }
+
+    #[test]
+    fn cuboid_set_reactor() {
+        // the small example from 2021 day 22
+        let mut reactor = CuboidSet::new();
+        reactor.insert(Cuboid::from_inclusive([10, 10, 10], [12, 12, 12]));
+        assert_eq!(reactor.volume(), 27);
+        reactor.insert(Cuboid::from_inclusive([11, 11, 11], [13, 13, 13]));
+        assert_eq!(reactor.volume(), 46);
+        reactor.remove(&Cuboid::from_inclusive([9, 9, 9], [11, 11, 11]));
+        assert_eq!(reactor.volume(), 38);
+        reactor.insert(Cuboid::from_inclusive([10, 10, 10], [10, 10, 10]));
+        assert_eq!(reactor.volume(), 39);
+        assert!(reactor.contains([10, 10, 10]) && !reactor.contains([11, 10, 10]));
+        let corner = reactor.intersection(&Cuboid::from_inclusive([12, 12, 12], [20, 20, 20]));
+        assert_eq!(corner.volume(), 8);
+    }
+}
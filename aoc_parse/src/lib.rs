@@ -0,0 +1,260 @@
+extern crate thiserror;
+use thiserror::Error;
+
+/// an error encountered while parsing puzzle input
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    #[error("grid row {0} has width {1} but the first row has width {2}")]
+    RaggedGrid(usize, usize, usize),
+}
+
+/// extract every signed integer from a line, ignoring everything else
+/// a '-' only counts as a sign when it doesn't follow a digit, so "2-4" gives 2 and 4 rather than 2 and -4
+pub fn ints(line: &str) -> Vec<i64> {
+    let bytes = line.as_bytes();
+    let mut found = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let negative = bytes[i] == b'-'
+            && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+            && (i == 0 || !bytes[i - 1].is_ascii_digit());
+        if negative || bytes[i].is_ascii_digit() {
+            let start = i;
+            i += 1;
+            while i < bytes.len() && bytes[i].is_ascii_digit() {
+                i += 1;
+            }
+            // numbers too big for an i64 are skipped rather than wrapped
+            if let Ok(n) = line[start..i].parse() {
+                found.push(n);
+            }
+        } else {
+            i += 1;
+        }
+    }
+    found
+}
+
+/// extract every unsigned integer from a line, ignoring everything else (including any '-')
+pub fn uints(line: &str) -> Vec<u64> {
+    line.split(|c: char| !c.is_ascii_digit())
+        .filter_map(|digits| digits.parse().ok())
+        .collect()
+}
+
+/// split the input into blocks separated by blank lines, e.g. the elves' inventories in 2022 day 1
+/// lines containing only whitespace count as blank and the blocks don't include their trailing newline
+pub fn blocks(input: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut start = None;
+    let mut end = 0;
+    let mut offset = 0;
+    for line in input.split_inclusive('\n') {
+        if line.trim().is_empty() {
+            if let Some(s) = start.take() {
+                found.push(&input[s..end]);
+            }
+        } else {
+            start.get_or_insert(offset);
+            end = offset + line.trim_end_matches(['\r', '\n']).len();
+        }
+        offset += line.len();
+    }
+    if let Some(s) = start {
+        found.push(&input[s..end]);
+    }
+    found
+}
+
+/// a rectangular grid of characters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CharGrid {
+    /// the characters, indexed by row then column
+    pub cells: Vec<Vec<char>>,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl CharGrid {
+    /// get the character at the given column and row if it's in bounds
+    pub fn get(&self, x: usize, y: usize) -> Option<char> {
+        self.cells.get(y).and_then(|row| row.get(x)).copied()
+    }
+
+    /// iterate over every character along with its column and row
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, char)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .flat_map(|(y, row)| row.iter().enumerate().map(move |(x, &c)| (x, y, c)))
+    }
+}
+
+/// parse a block of text into a character grid, one row per line
+/// trailing blank lines are ignored but every row must be as wide as the first
+pub fn char_grid(input: &str) -> Result<CharGrid, ParseError> {
+    let cells: Vec<Vec<char>> = input
+        .trim_end()
+        .lines()
+        .map(|line| line.chars().collect())
+        .collect();
+
+    let width = cells.first().map_or(0, Vec::len);
+    for (y, row) in cells.iter().enumerate() {
+        if row.len() != width {
+            return Err(ParseError::RaggedGrid(y, row.len(), width));
+        }
+    }
+
+    Ok(CharGrid {
+        height: cells.len(),
+        width,
+        cells,
+    })
+}
+
+/// split a line into the parts matching each `{}` in the pattern, or None if the literal text doesn't match
+/// each `{}` matches as little as possible up to the next piece of literal text, except the last one which
+/// takes everything up to the end of the pattern. this is the engine behind `scan!`
+pub fn scan_fields<'a>(line: &'a str, pattern: &str) -> Option<Vec<&'a str>> {
+    let mut literals = pattern.split("{}");
+    let mut rest = line.strip_prefix(literals.next().unwrap_or(""))?;
+    let literals: Vec<&str> = literals.collect();
+
+    let mut fields = Vec::with_capacity(literals.len());
+    for (i, literal) in literals.iter().enumerate() {
+        if i == literals.len() - 1 {
+            fields.push(rest.strip_suffix(literal)?);
+        } else if literal.is_empty() {
+            // two placeholders in a row can't be told apart, so the first one gets nothing
+            fields.push("");
+        } else {
+            let (field, after) = rest.split_once(literal)?;
+            fields.push(field);
+            rest = after;
+        }
+    }
+
+    Some(fields)
+}
+
+/// parse a line against a pattern where each `{}` is a value of the corresponding type, returning a tuple
+/// of the values or None if the line doesn't match or a value doesn't parse
+///
+/// ```
+/// use aoc_parse::scan;
+///
+/// let parsed = scan!("move 3 from 1 to 2", "move {} from {} to {}" => usize, usize, usize);
+/// assert_eq!(parsed, Some((3, 1, 2)));
+/// ```
+#[macro_export]
+macro_rules! scan {
+    ($line:expr, $pattern:expr => $($t:ty),+ $(,)?) => {
+        $crate::scan_fields($line, $pattern).and_then(|fields| {
+            let mut fields = fields.into_iter();
+            let parsed = ($(fields.next()?.trim().parse::<$t>().ok()?,)+);
+            // make sure the pattern didn't have more placeholders than types
+            match fields.next() {
+                Some(_) => None,
+                None => Some(parsed),
+            }
+        })
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ints_reads_signs_only_before_digits() {
+        assert_eq!(ints("x=-3, y=12 dx=-0"), vec![-3, 12, 0]);
+        assert_eq!(ints("2-4,6-8"), vec![2, 4, 6, 8]);
+        assert_eq!(ints("a - 5 --7"), vec![5, -7]);
+        assert_eq!(ints("no numbers here"), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn ints_skips_values_that_overflow() {
+        assert_eq!(ints("1 99999999999999999999 -2"), vec![1, -2]);
+        assert_eq!(
+            ints("9223372036854775807 -9223372036854775808"),
+            vec![i64::MAX, i64::MIN]
+        );
+    }
+
+    #[test]
+    fn uints_ignore_signs() {
+        assert_eq!(uints("x=-3, y=12"), vec![3, 12]);
+        assert_eq!(uints("2-4"), vec![2, 4]);
+        assert_eq!(
+            uints("18446744073709551615 18446744073709551616"),
+            vec![u64::MAX]
+        );
+    }
+
+    #[test]
+    fn blocks_split_on_blank_lines() {
+        assert_eq!(blocks("1\n2\n\n3\n"), vec!["1\n2", "3"]);
+        assert_eq!(blocks("\n\n1\n\n\n2\n\n\n"), vec!["1", "2"]);
+        assert_eq!(blocks(""), Vec::<&str>::new());
+    }
+
+    #[test]
+    fn blocks_handle_crlf_and_whitespace_lines() {
+        assert_eq!(blocks("1\r\n2\r\n\r\n3\r\n"), vec!["1\r\n2", "3"]);
+        assert_eq!(blocks("a\n  \t\nb\n \n"), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn char_grid_reads_rows() {
+        let grid = char_grid("ab\ncd\n\n").unwrap();
+        assert_eq!((grid.width, grid.height), (2, 2));
+        assert_eq!(grid.get(1, 0), Some('b'));
+        assert_eq!(grid.get(2, 0), None);
+        assert_eq!(grid.iter().nth(2), Some((0, 1, 'c')));
+    }
+
+    #[test]
+    fn char_grid_rejects_ragged_rows() {
+        assert_eq!(
+            char_grid("abc\nab\nabc"),
+            Err(ParseError::RaggedGrid(1, 2, 3))
+        );
+    }
+
+    #[test]
+    fn scan_fields_split_on_literals() {
+        assert_eq!(
+            scan_fields("Sensor at x=2, y=18", "Sensor at x={}, y={}"),
+            Some(vec!["2", "18"])
+        );
+        assert_eq!(scan_fields("Beacon at x=2", "Sensor at x={}"), None);
+        assert_eq!(scan_fields("x=2; y=3", "x={}, y={}"), None);
+        assert_eq!(scan_fields("a-b.", "{}-{}!"), None);
+    }
+
+    #[test]
+    fn scan_fields_give_adjacent_placeholders_nothing() {
+        assert_eq!(scan_fields("ab", "{}{}"), Some(vec!["", "ab"]));
+    }
+
+    #[test]
+    fn scan_parses_fields() {
+        assert_eq!(
+            scan!("Blueprint 3: 4 ore", "Blueprint {}: {} {}" => u32, i64, String),
+            Some((3, 4, "ore".to_string()))
+        );
+        assert_eq!(
+            scan!("move x from 1", "move {} from {}" => usize, usize),
+            None
+        );
+        assert_eq!(
+            scan!("move 3 to 1", "move {} from {}" => usize, usize),
+            None
+        );
+        // a pattern with more placeholders than types doesn't match
+        assert_eq!(scan!("1 2", "{} {}" => u8), None);
+        assert_eq!(scan!("12", "{}{}" => String, u8), Some((String::new(), 12)));
+    }
+}
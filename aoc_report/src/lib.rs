@@ -0,0 +1,192 @@
+use std::path::PathBuf;
+
+extern crate serde;
+use serde::Serialize;
+
+extern crate serde_json;
+
+/// the kind of problem that stopped a run, so tools don't have to make sense of the error message
+/// each kind has its own exit code so wrapper scripts can react to them differently:
+///
+/// | code | kind                     |
+/// |------|--------------------------|
+/// | 0    | (success)                |
+/// | 1    | other                    |
+/// | 2    | (usage error, from clap) |
+/// | 3    | session problem          |
+/// | 4    | network failure          |
+/// | 5    | not yet unlocked         |
+/// | 6    | unauthorized             |
+/// | 7    | example not found        |
+/// | 8    | wrong answer             |
+/// | 9    | rate limited             |
+/// | 10   | io error                 |
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// the session cookie couldn't be found or isn't logged in
+    Session,
+    /// adventofcode.com couldn't be reached or answered with a server error
+    Network,
+    /// the puzzle hasn't unlocked yet
+    NotUnlocked,
+    /// adventofcode.com refused the request
+    Unauthorized,
+    /// the example or its answer couldn't be found on the puzzle page
+    ExampleNotFound,
+    /// the answer doesn't match the recorded one
+    WrongAnswer,
+    /// adventofcode.com asked to wait before trying again
+    RateLimited,
+    /// a file or stdout couldn't be read or written
+    Io,
+    /// anything else
+    Other,
+}
+
+impl ErrorKind {
+    /// the kind of problem behind a bad response from adventofcode.com, given its status code
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            404 => ErrorKind::NotUnlocked,
+            400 | 401 | 403 => ErrorKind::Unauthorized,
+            429 => ErrorKind::RateLimited,
+            _ => ErrorKind::Network,
+        }
+    }
+
+    /// the code to exit with, as in the table above
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorKind::Other => 1,
+            ErrorKind::Session => 3,
+            ErrorKind::Network => 4,
+            ErrorKind::NotUnlocked => 5,
+            ErrorKind::Unauthorized => 6,
+            ErrorKind::ExampleNotFound => 7,
+            ErrorKind::WrongAnswer => 8,
+            ErrorKind::RateLimited => 9,
+            ErrorKind::Io => 10,
+        }
+    }
+}
+
+/// which puzzle a run was about
+#[derive(Serialize)]
+pub struct Puzzle {
+    pub year: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub day: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<u8>,
+}
+
+/// the error that stopped a run
+#[derive(Serialize)]
+pub struct ErrorReport {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+/// what happened during a run, printed as a line of json with --json
+#[derive(Serialize)]
+pub struct Report {
+    pub action: &'static str,
+    pub puzzle: Option<Puzzle>,
+    pub account: Option<String>,
+    /// "ok" or "error", or adventofcode.com's verdict on a submitted answer
+    pub outcome: String,
+    /// what would have been printed without --json
+    pub output: Option<String>,
+    /// every file written during the run
+    pub paths: Vec<PathBuf>,
+    /// how long adventofcode.com asked to wait before submitting another answer
+    pub wait_seconds: Option<u64>,
+    pub error: Option<ErrorReport>,
+}
+
+impl Report {
+    pub fn new(action: &'static str, puzzle: Option<Puzzle>, account: Option<String>) -> Self {
+        Report {
+            action,
+            puzzle,
+            account,
+            outcome: "ok".to_string(),
+            output: None,
+            paths: Vec::new(),
+            wait_seconds: None,
+            error: None,
+        }
+    }
+
+    /// fill in what the run printed and the error that stopped it, if there was one
+    /// (a submitted answer's verdict is kept as the outcome even if it's treated as an error)
+    pub fn finish(&mut self, output: String, error: Option<(ErrorKind, String)>) {
+        if !output.is_empty() {
+            self.output = Some(output);
+        }
+        if let Some((kind, message)) = error {
+            if self.outcome == "ok" {
+                self.outcome = "error".to_string();
+            }
+            self.error = Some(ErrorReport { kind, message });
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("couldn't serialize report")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_codes() {
+        let kinds = [
+            (ErrorKind::Other, 1),
+            (ErrorKind::Session, 3),
+            (ErrorKind::Network, 4),
+            (ErrorKind::NotUnlocked, 5),
+            (ErrorKind::Unauthorized, 6),
+            (ErrorKind::ExampleNotFound, 7),
+            (ErrorKind::WrongAnswer, 8),
+            (ErrorKind::RateLimited, 9),
+            (ErrorKind::Io, 10),
+        ];
+        for (kind, code) in kinds {
+            assert_eq!(kind.exit_code(), code, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn status_codes() {
+        assert_eq!(ErrorKind::from_status(404), ErrorKind::NotUnlocked);
+        for status in [400, 401, 403] {
+            assert_eq!(ErrorKind::from_status(status), ErrorKind::Unauthorized);
+        }
+        assert_eq!(ErrorKind::from_status(429), ErrorKind::RateLimited);
+        for status in [302, 500, 503] {
+            assert_eq!(ErrorKind::from_status(status), ErrorKind::Network);
+        }
+    }
+
+    #[test]
+    fn json() {
+        let puzzle = Puzzle {
+            year: 2023,
+            day: Some(5),
+            level: None,
+        };
+        let mut report = Report::new("fetch", Some(puzzle), None);
+        report.finish(
+            String::new(),
+            Some((ErrorKind::NotUnlocked, "not yet".to_string())),
+        );
+        assert_eq!(
+            report.to_json(),
+            r#"{"action":"fetch","puzzle":{"year":2023,"day":5},"account":null,"outcome":"error","output":null,"paths":[],"wait_seconds":null,"error":{"kind":"not_unlocked","message":"not yet"}}"#
+        );
+    }
+}
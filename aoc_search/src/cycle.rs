@@ -0,0 +1,214 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// where a sequence of states starts repeating: the state after `start` steps is the first one that
+/// comes back, and it comes back every `length` steps from then on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+impl Cycle {
+    /// the earliest step with the same state as step n
+    pub fn equivalent(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.length
+        }
+    }
+}
+
+/// find the cycle in the sequence of states by remembering every state seen so far
+/// the sequence must eventually repeat or this will never return
+pub fn find<S, F>(initial: S, mut step: F) -> Cycle
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let mut seen = HashMap::new();
+    let mut state = initial;
+    for n in 0.. {
+        if let Some(start) = seen.insert(state.clone(), n) {
+            return Cycle {
+                start,
+                length: n - start,
+            };
+        }
+        state = step(&state);
+    }
+    unreachable!("ran out of steps before finding a cycle")
+}
+
+/// find the cycle in the sequence of states with brent's algorithm, which only keeps two states in memory
+/// and doesn't need them to be hashable, at the cost of running the step function a few more times
+/// the sequence must eventually repeat or this will never return
+pub fn brent<S, F>(initial: S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // find the length by teleporting the tortoise to the hare every power of two steps
+    let mut power = 1;
+    let mut length = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == length {
+            tortoise = hare.clone();
+            power *= 2;
+            length = 0;
+        }
+        hare = step(&hare);
+        length += 1;
+    }
+
+    // then find the start by walking two states a cycle length apart until they meet
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..length {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+
+    Cycle { start, length }
+}
+
+/// the state after n steps, skipping ahead as soon as the sequence starts repeating
+/// e.g. for puzzles asking about the state "after 1000000000 cycles"
+pub fn state_at<S, F>(initial: S, mut step: F, n: usize) -> S
+where
+    S: Clone + Eq + Hash,
+    F: FnMut(&S) -> S,
+{
+    let mut seen = HashMap::new();
+    let mut history = Vec::new();
+    let mut state = initial;
+    for i in 0..n {
+        if let Some(&start) = seen.get(&state) {
+            let cycle = Cycle {
+                start,
+                length: i - start,
+            };
+            return history.swap_remove(cycle.equivalent(n));
+        }
+        seen.insert(state.clone(), i);
+        history.push(state.clone());
+        state = step(&state);
+    }
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a sequence with a tail before it repeats, different for each modulus and starting value
+    fn step(modulus: u64) -> impl Fn(&u64) -> u64 {
+        move |&x| (x * x + 1) % modulus
+    }
+
+    /// the state after n steps, one step at a time
+    fn brute_force(initial: u64, modulus: u64, n: usize) -> u64 {
+        (0..n).fold(initial, |x, _| step(modulus)(&x))
+    }
+
+    #[test]
+    fn find_and_brent_agree() {
+        for modulus in [7, 100, 255, 1009, 65537] {
+            for initial in 0..20 {
+                let found = find(initial, step(modulus));
+                assert_eq!(
+                    brent(initial, step(modulus)),
+                    found,
+                    "{initial} mod {modulus}"
+                );
+                let repeat = brute_force(initial, modulus, found.start);
+                assert_eq!(brute_force(repeat, modulus, found.length), repeat);
+                // the state before the cycle starts isn't part of it
+                if found.start > 0 {
+                    let before = brute_force(initial, modulus, found.start - 1);
+                    assert_ne!(brute_force(before, modulus, found.length), before);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fixed_point() {
+        assert_eq!(
+            find(3, |&x| x),
+            Cycle {
+                start: 0,
+                length: 1
+            }
+        );
+        assert_eq!(
+            brent(3, |&x| x),
+            Cycle {
+                start: 0,
+                length: 1
+            }
+        );
+        assert_eq!(
+            find(5, |&x: &u64| x.min(4)),
+            Cycle {
+                start: 1,
+                length: 1
+            }
+        );
+        assert_eq!(
+            brent(5, |&x: &u64| x.min(4)),
+            Cycle {
+                start: 1,
+                length: 1
+            }
+        );
+    }
+
+    #[test]
+    fn state_at_matches_brute_force() {
+        let modulus = 1009;
+        let initial = 3;
+        let cycle = find(initial, step(modulus));
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 13,
+                length: 49
+            }
+        );
+        assert_eq!(state_at(initial, step(modulus), 0), initial);
+        for n in [
+            1,
+            cycle.start - 1,
+            cycle.start,
+            cycle.start + cycle.length,
+            10_000,
+            123_457,
+        ] {
+            assert_eq!(
+                state_at(initial, step(modulus), n),
+                brute_force(initial, modulus, n),
+                "{n}"
+            );
+        }
+    }
+
+    #[test]
+    fn state_at_far_ahead() {
+        let modulus = 1009;
+        let initial = 3;
+        let cycle = find(initial, step(modulus));
+        let n = 1_000_000_000;
+        let expected = brute_force(initial, modulus, cycle.equivalent(n));
+        assert_eq!(state_at(initial, step(modulus), n), expected);
+        assert_eq!(cycle.equivalent(n + cycle.length), cycle.equivalent(n));
+    }
+}
@@ -0,0 +1,506 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+pub mod cycle;
+
+/// the path from the start to the node by following each node's parent back to the start
+fn reconstruct<N: Clone + Eq + Hash>(parents: &HashMap<N, N>, end: N) -> Vec<N> {
+    let mut path = vec![end];
+    while let Some(parent) = parents.get(path.last().unwrap()) {
+        path.push(parent.clone());
+    }
+    path.reverse();
+    path
+}
+
+/// breadth first search from the start to the first node satisfying the goal
+/// returns the shortest path (by number of steps) including both the start and the goal
+pub fn bfs<N, FN, IN, FG>(start: N, mut neighbors: FN, mut is_goal: FG) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    let mut parents = HashMap::new();
+    let mut seen = HashSet::from([start.clone()]);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(node) = queue.pop_front() {
+        if is_goal(&node) {
+            return Some(reconstruct(&parents, node));
+        }
+        for next in neighbors(&node) {
+            if seen.insert(next.clone()) {
+                parents.insert(next.clone(), node.clone());
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// breadth first search from the start to every reachable node, returning the number of steps to each one
+pub fn bfs_distances<N, FN, IN>(start: N, mut neighbors: FN) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut distances = HashMap::from([(start.clone(), 0)]);
+    let mut queue = VecDeque::from([(start, 0)]);
+
+    while let Some((node, distance)) = queue.pop_front() {
+        for next in neighbors(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), distance + 1);
+                queue.push_back((next, distance + 1));
+            }
+        }
+    }
+
+    distances
+}
+
+/// depth first search from the start to the first node satisfying the goal
+/// returns the path that was found including both the start and the goal, which isn't necessarily the shortest
+pub fn dfs<N, FN, IN, FG>(start: N, mut neighbors: FN, mut is_goal: FG) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FG: FnMut(&N) -> bool,
+{
+    if is_goal(&start) {
+        return Some(vec![start]);
+    }
+
+    // the path so far along with the neighbours we haven't tried yet for each node on it
+    let mut seen = HashSet::from([start.clone()]);
+    let mut stack = vec![neighbors(&start).into_iter()];
+    let mut path = vec![start];
+
+    while let Some(untried) = stack.last_mut() {
+        if let Some(next) = untried.next() {
+            if !seen.insert(next.clone()) {
+                continue;
+            }
+            if is_goal(&next) {
+                path.push(next);
+                return Some(path);
+            }
+            stack.push(neighbors(&next).into_iter());
+            path.push(next);
+        } else {
+            stack.pop();
+            path.pop();
+        }
+    }
+
+    None
+}
+
+/// the bookkeeping shared by dijkstra and A*: nodes are numbered so the heap doesn't need them to be Ord
+struct Frontier<N, C> {
+    nodes: Vec<N>,
+    indices: HashMap<N, usize>,
+    costs: Vec<C>,
+    parents: Vec<Option<usize>>,
+    heap: BinaryHeap<Reverse<(C, C, usize)>>,
+}
+
+impl<N, C> Frontier<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord,
+{
+    fn new(start: N, zero: C) -> Self {
+        Frontier {
+            nodes: vec![start.clone()],
+            indices: HashMap::from([(start, 0)]),
+            costs: vec![zero],
+            parents: vec![None],
+            heap: BinaryHeap::from([Reverse((zero, zero, 0))]),
+        }
+    }
+
+    /// the cheapest node still to visit and its cost, skipping stale heap entries
+    fn pop(&mut self) -> Option<(usize, C)> {
+        while let Some(Reverse((_, cost, index))) = self.heap.pop() {
+            if cost == self.costs[index] {
+                return Some((index, cost));
+            }
+        }
+        None
+    }
+
+    /// record a route to the node through the parent if it's cheaper than what we've seen so far
+    fn relax(&mut self, node: N, parent: usize, cost: C, estimate: C) {
+        let index = match self.indices.get(&node) {
+            Some(&index) if self.costs[index] <= cost => return,
+            Some(&index) => {
+                self.costs[index] = cost;
+                self.parents[index] = Some(parent);
+                index
+            }
+            None => {
+                let index = self.nodes.len();
+                self.indices.insert(node.clone(), index);
+                self.nodes.push(node);
+                self.costs.push(cost);
+                self.parents.push(Some(parent));
+                index
+            }
+        };
+        self.heap.push(Reverse((estimate, cost, index)));
+    }
+
+    /// the path from the start to the node at the index
+    fn path(&self, mut index: usize) -> Vec<N> {
+        let mut path = vec![self.nodes[index].clone()];
+        while let Some(parent) = self.parents[index] {
+            path.push(self.nodes[parent].clone());
+            index = parent;
+        }
+        path.reverse();
+        path
+    }
+}
+
+/// dijkstra's algorithm from the start to the first node satisfying the goal
+/// neighbours are given along with the (non-negative) cost of moving to them, and C::default() must be zero
+/// returns the cheapest path including both the start and the goal along with its total cost
+pub fn dijkstra<N, C, FN, IN, FG>(start: N, neighbors: FN, is_goal: FG) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FG: FnMut(&N) -> bool,
+{
+    astar(start, neighbors, |_| C::default(), is_goal)
+}
+
+/// A* search from the start to the first node satisfying the goal
+/// neighbours are given along with the (non-negative) cost of moving to them, and C::default() must be zero
+/// the heuristic must never overestimate the remaining cost to the goal or the path may not be the cheapest
+/// returns the cheapest path including both the start and the goal along with its total cost
+pub fn astar<N, C, FN, IN, FH, FG>(
+    start: N,
+    mut neighbors: FN,
+    mut heuristic: FH,
+    mut is_goal: FG,
+) -> Option<(Vec<N>, C)>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FG: FnMut(&N) -> bool,
+{
+    let mut frontier = Frontier::new(start, C::default());
+
+    while let Some((index, cost)) = frontier.pop() {
+        let node = frontier.nodes[index].clone();
+        if is_goal(&node) {
+            return Some((frontier.path(index), cost));
+        }
+        for (next, step) in neighbors(&node) {
+            let next_cost = cost + step;
+            let estimate = next_cost + heuristic(&next);
+            frontier.relax(next, index, next_cost, estimate);
+        }
+    }
+
+    None
+}
+
+/// split the nodes into groups that are connected to each other
+/// neighbours are assumed to go both ways, and any neighbours that aren't in the list of nodes are included too
+/// components are in the order of the first node of each in the list, and each starts with that node
+pub fn connected_components<N, FN, IN>(
+    nodes: impl IntoIterator<Item = N>,
+    mut neighbors: FN,
+) -> Vec<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    let mut seen = HashSet::new();
+    let mut components = Vec::new();
+
+    for node in nodes {
+        if !seen.insert(node.clone()) {
+            continue;
+        }
+        let mut component = vec![node];
+        let mut next_unexplored = 0;
+        while next_unexplored < component.len() {
+            for next in neighbors(&component[next_unexplored]) {
+                if seen.insert(next.clone()) {
+                    component.push(next);
+                }
+            }
+            next_unexplored += 1;
+        }
+        components.push(component);
+    }
+
+    components
+}
+
+/// order the nodes so that every node comes before all of its successors
+/// any successors that aren't in the list of nodes are included too
+/// where there's a choice, nodes come out in the order they were given (then the order they were discovered)
+/// returns None if the graph has a cycle
+pub fn topological_sort<N, FN, IN>(
+    nodes: impl IntoIterator<Item = N>,
+    mut successors: FN,
+) -> Option<Vec<N>>
+where
+    N: Clone + Eq + Hash,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+{
+    // number every node and find the successors of each one
+    let mut all: Vec<N> = Vec::new();
+    let mut indices = HashMap::new();
+    for node in nodes {
+        if !indices.contains_key(&node) {
+            indices.insert(node.clone(), all.len());
+            all.push(node);
+        }
+    }
+    let mut edges: Vec<Vec<usize>> = Vec::new();
+    while edges.len() < all.len() {
+        let mut targets = Vec::new();
+        for next in successors(&all[edges.len()]) {
+            let index = *indices.entry(next.clone()).or_insert_with(|| {
+                all.push(next);
+                all.len() - 1
+            });
+            targets.push(index);
+        }
+        edges.push(targets);
+    }
+
+    // kahn's algorithm: repeatedly take the earliest node with nothing left pointing to it
+    let mut incoming = vec![0; all.len()];
+    for &target in edges.iter().flatten() {
+        incoming[target] += 1;
+    }
+    let mut ready: BinaryHeap<Reverse<usize>> = (0..all.len())
+        .filter(|&i| incoming[i] == 0)
+        .map(Reverse)
+        .collect();
+
+    let mut order = Vec::with_capacity(all.len());
+    while let Some(Reverse(index)) = ready.pop() {
+        order.push(all[index].clone());
+        for &target in &edges[index] {
+            incoming[target] -= 1;
+            if incoming[target] == 0 {
+                ready.push(Reverse(target));
+            }
+        }
+    }
+
+    if order.len() == all.len() {
+        Some(order)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// S at the top left, E at (7, 2) 11 steps away, and (1, 5) walled off
+    const MAZE: [&str; 6] = [
+        "S.#.....", ".##.###.", "....#..E", ".##...#.", "###.##.#", "#.#.....",
+    ];
+    const START: (usize, usize) = (0, 0);
+    const END: (usize, usize) = (7, 2);
+
+    fn open((x, y): (usize, usize)) -> bool {
+        MAZE.get(y)
+            .and_then(|row| row.as_bytes().get(x))
+            .is_some_and(|&c| c != b'#')
+    }
+
+    fn steps(&(x, y): &(usize, usize)) -> Vec<(usize, usize)> {
+        [
+            (x, y.wrapping_sub(1)),
+            (x + 1, y),
+            (x, y + 1),
+            (x.wrapping_sub(1), y),
+        ]
+        .into_iter()
+        .filter(|&next| open(next))
+        .collect()
+    }
+
+    /// every step of the path is a move to an open neighbour, and no cell is visited twice
+    fn assert_walkable(path: &[(usize, usize)]) {
+        assert_eq!(path.first(), Some(&START));
+        assert_eq!(path.last(), Some(&END));
+        for pair in path.windows(2) {
+            assert!(steps(&pair[0]).contains(&pair[1]), "{pair:?} isn't a step");
+        }
+        assert_eq!(path.iter().collect::<HashSet<_>>().len(), path.len());
+    }
+
+    #[test]
+    fn bfs_maze() {
+        let path = bfs(START, steps, |&node| node == END).unwrap();
+        assert_eq!(path.len(), 12);
+        assert_walkable(&path);
+        assert_eq!(bfs(START, steps, |&node| node == (1, 5)), None);
+        assert_eq!(bfs(START, steps, |&node| node == START), Some(vec![START]));
+    }
+
+    #[test]
+    fn bfs_distances_maze() {
+        let distances = bfs_distances(START, steps);
+        assert_eq!(distances.len(), 29);
+        assert_eq!(distances[&START], 0);
+        assert_eq!(distances[&(0, 3)], 3);
+        assert_eq!(distances[&END], 11);
+        assert!(!distances.contains_key(&(1, 5)));
+    }
+
+    #[test]
+    fn dfs_maze() {
+        let path = dfs(START, steps, |&node| node == END).unwrap();
+        assert!(path.len() >= 12);
+        assert_walkable(&path);
+        assert_eq!(dfs(START, steps, |&node| node == (1, 5)), None);
+    }
+
+    /// the risk levels from 2021 day 15, where the cheapest path from the top left to the bottom right costs 40
+    const RISKS: [&str; 10] = [
+        "1163751742",
+        "1381373672",
+        "2136511328",
+        "3694931569",
+        "7463417111",
+        "1319128137",
+        "1359912421",
+        "3125421639",
+        "1293138521",
+        "2311944581",
+    ];
+
+    fn risky_steps(&(x, y): &(usize, usize)) -> Vec<((usize, usize), u32)> {
+        [
+            (x, y.wrapping_sub(1)),
+            (x + 1, y),
+            (x, y + 1),
+            (x.wrapping_sub(1), y),
+        ]
+        .into_iter()
+        .filter_map(|(x, y)| {
+            let risk = RISKS.get(y)?.as_bytes().get(x)?;
+            Some(((x, y), (risk - b'0') as u32))
+        })
+        .collect()
+    }
+
+    /// the cost of a path is the sum of the risks of every cell entered
+    fn path_cost(path: &[(usize, usize)]) -> u32 {
+        path.windows(2)
+            .map(|pair| {
+                let steps = risky_steps(&pair[0]);
+                steps.iter().find(|(next, _)| *next == pair[1]).unwrap().1
+            })
+            .sum()
+    }
+
+    #[test]
+    fn dijkstra_risks() {
+        let (path, cost) = dijkstra((0, 0), risky_steps, |&node| node == (9, 9)).unwrap();
+        assert_eq!(cost, 40);
+        assert_eq!(path_cost(&path), 40);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(9, 9)));
+    }
+
+    #[test]
+    fn astar_risks() {
+        let manhattan = |&(x, y): &(usize, usize)| (9 - x + 9 - y) as u32;
+        let (path, cost) = astar((0, 0), risky_steps, manhattan, |&node| node == (9, 9)).unwrap();
+        assert_eq!(cost, 40);
+        assert_eq!(path_cost(&path), 40);
+        assert_eq!(path.last(), Some(&(9, 9)));
+    }
+
+    #[test]
+    fn weighted_maze() {
+        let unit_steps = |node: &(usize, usize)| steps(node).into_iter().map(|next| (next, 1));
+        let (path, cost) = dijkstra(START, unit_steps, |&node| node == END).unwrap();
+        assert_eq!(cost, 11);
+        assert_walkable(&path);
+        let nowhere: Option<(Vec<_>, u32)> = dijkstra(START, unit_steps, |&node| node == (1, 5));
+        assert_eq!(nowhere, None);
+    }
+
+    /// the nodes joined to the node by an edge going either way
+    fn linked<N: Copy + Eq>(edges: &[(N, N)], node: &N) -> Vec<N> {
+        edges
+            .iter()
+            .filter_map(|&(a, b)| {
+                if a == *node {
+                    Some(b)
+                } else if b == *node {
+                    Some(a)
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn components() {
+        let edges = [(1, 2), (2, 3), (4, 5), (3, 7)];
+        let components = connected_components(1..=6, |node| linked(&edges, node));
+        assert_eq!(components, vec![vec![1, 2, 3, 7], vec![4, 5], vec![6]]);
+    }
+
+    /// the nodes the edges lead to from the node
+    fn successors(edges: &[(char, char)], node: &char) -> Vec<char> {
+        edges
+            .iter()
+            .filter(|(from, _)| from == node)
+            .map(|&(_, to)| to)
+            .collect()
+    }
+
+    #[test]
+    fn topological_sort_dag() {
+        let edges = [('a', 'c'), ('a', 'b'), ('b', 'd'), ('c', 'd')];
+        let order = topological_sort(['a', 'b', 'c', 'd'], |node| successors(&edges, node));
+        assert_eq!(order, Some(vec!['a', 'b', 'c', 'd']));
+        // ties go to the node that was given first
+        let order = topological_sort(['d', 'c', 'b', 'a'], |node| successors(&edges, node));
+        assert_eq!(order, Some(vec!['a', 'c', 'b', 'd']));
+        // successors that weren't given are found along the way
+        let order = topological_sort(['a'], |node| successors(&edges, node));
+        assert_eq!(order, Some(vec!['a', 'c', 'b', 'd']));
+    }
+
+    #[test]
+    fn topological_sort_cycle() {
+        let edges = [('x', 'a'), ('a', 'b'), ('b', 'c'), ('c', 'a')];
+        assert_eq!(
+            topological_sort(['x'], |node| successors(&edges, node)),
+            None
+        );
+    }
+}
@@ -19,6 +19,9 @@ use reqwest::StatusCode;
 extern crate regex;
 use regex::Regex;
 
+extern crate aoc_report as report;
+use report::{Puzzle, Report};
+
 #[derive(Parser)]
 struct Args {
     /// optional file to output to (defaults to stdout)
@@ -32,6 +35,19 @@ struct Args {
     /// year to download the example for (defaults to current year if it's Decembe in UTC-5, otherwise last year)
     #[arg(value_parser = clap::value_parser!(u16).range(2015..), short, long)]
     year: Option<u16>,
+
+    /// print the expected answer to the example instead of the example itself
+    #[arg(short, long)]
+    answer: bool,
+
+    /// level of the puzzle to find the expected answer for (1 or 2, defaults to 1)
+    /// level 2 is only on the page once level 1 is solved, so it usually won't be found
+    #[arg(default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2), short, long)]
+    level: u8,
+
+    /// print a json object describing what happened instead of plain text
+    #[arg(long)]
+    json: bool,
 }
 
 /// output configration options
@@ -39,9 +55,16 @@ enum OutputCfg {
     File(PathBuf),
     Stdout,
 }
+/// what to retrieve from the page
+enum TargetCfg {
+    Example,
+    Answer(u8),
+}
 /// configuration settings for the application
 pub struct Config {
     out: OutputCfg,
+    target: TargetCfg,
+    json: bool,
     day: u8,
     year: u16,
 }
@@ -57,6 +80,13 @@ impl Config {
             None => OutputCfg::Stdout,
         };
 
+        // are we after the example or its answer?
+        let target = if args.answer {
+            TargetCfg::Answer(args.level)
+        } else {
+            TargetCfg::Example
+        };
+
         // time sensitive config
         let aoc_dt = get_aoc_dt();
 
@@ -102,7 +132,13 @@ impl Config {
             1
         };
 
-        Config { out, day, year }
+        Config {
+            out,
+            target,
+            json: args.json,
+            day,
+            year,
+        }
     }
 }
 
@@ -124,24 +160,76 @@ pub enum RunError {
     BadRequest(u16),
     #[error("failed to find example on page")]
     RegexFailed,
+    #[error("failed to find the example's answer for level {0} on page")]
+    AnswerRegexFailed(u8),
     #[error("failed to write example to {0}: {1}")]
     FileWriteFailed(PathBuf, io::Error),
     #[error("failed to write example to stdout: {0}")]
     StdoutWriteFailed(io::Error),
 }
 
+impl RunError {
+    /// what kind of problem this is, for --json
+    pub fn kind(&self) -> report::ErrorKind {
+        match self {
+            RunError::RequestFailed(_) => report::ErrorKind::Network,
+            RunError::BadRequest(status) => report::ErrorKind::from_status(*status),
+            RunError::RegexFailed | RunError::AnswerRegexFailed(_) => {
+                report::ErrorKind::ExampleNotFound
+            }
+            RunError::FileWriteFailed(_, _) | RunError::StdoutWriteFailed(_) => {
+                report::ErrorKind::Io
+            }
+        }
+    }
+}
+
 /// run the application according to the provided config
 pub fn run(cfg: Config) -> Result<(), RunError> {
+    let (action, level) = match cfg.target {
+        TargetCfg::Example => ("example", None),
+        TargetCfg::Answer(level) => ("example_answer", Some(level)),
+    };
+    let puzzle = Puzzle {
+        year: cfg.year,
+        day: Some(cfg.day),
+        level,
+    };
+    // aocex doesn't use a session cookie, so there's never an account
+    let mut report = Report::new(action, Some(puzzle), None);
+
+    let result = retrieve(&cfg, &mut report);
+    if !cfg.json {
+        return result;
+    }
+
+    // the output is already in the report if there was any
+    let error = result.as_ref().err().map(|e| (e.kind(), e.to_string()));
+    report.finish(String::new(), error);
+    io::stdout()
+        .write_all(format!("{}\n", report.to_json()).as_bytes())
+        .map_err(RunError::StdoutWriteFailed)?;
+    result
+}
+
+/// find the example or its answer on the page and write it where the config says to
+/// (or into the report with --json, instead of stdout)
+fn retrieve(cfg: &Config, report: &mut Report) -> Result<(), RunError> {
     let html = get_html(cfg.year, cfg.day)?;
-    let example = retrieve_example(html)?;
+    let found = match cfg.target {
+        TargetCfg::Example => retrieve_example(html)?,
+        TargetCfg::Answer(level) => retrieve_answer(html, level)?,
+    };
 
-    match cfg.out {
+    match &cfg.out {
         OutputCfg::File(f) => {
-            fs::write(&f, example).map_err(|e| RunError::FileWriteFailed(f, e))?;
+            fs::write(f, found).map_err(|e| RunError::FileWriteFailed(f.clone(), e))?;
+            report.paths.push(f.clone());
         }
+        OutputCfg::Stdout if cfg.json => report.output = Some(found),
         OutputCfg::Stdout => {
             io::stdout()
-                .write_all(example.as_bytes())
+                .write_all(found.as_bytes())
                 .map_err(RunError::StdoutWriteFailed)?;
         }
     };
@@ -155,7 +243,7 @@ fn get_html(year: u16, day: u8) -> Result<String, RunError> {
     let response = blocking::get(url).map_err(RunError::RequestFailed)?;
 
     match response.status() {
-        StatusCode::OK => Ok(response.text().unwrap()),
+        StatusCode::OK => response.text().map_err(RunError::RequestFailed),
         other => Err(RunError::BadRequest(other.as_u16())),
     }
 }
@@ -178,3 +266,48 @@ fn retrieve_example(html: String) -> Result<String, RunError> {
         Err(RunError::RegexFailed)
     }
 }
+
+/// given the page's html, retrieve the expected answer to the example for the given level
+/// (the last emphasized code element in that level's description, which is where the answer almost always is)
+fn retrieve_answer(html: String, level: u8) -> Result<String, RunError> {
+    const ARTICLE_PATTERN: &str = r#"(?s)<article class="day-desc">.*?<\/article>"#;
+    const ANSWER_PATTERN: &str = r"<code><em>([^<]*)<\/em><\/code>";
+    let article_reg = Regex::new(ARTICLE_PATTERN).unwrap();
+    let answer_reg = Regex::new(ANSWER_PATTERN).unwrap();
+
+    let article = article_reg
+        .find_iter(&html)
+        .nth(level as usize - 1)
+        .ok_or(RunError::AnswerRegexFailed(level))?;
+
+    if let Some(caps) = answer_reg.captures_iter(article.as_str()).last() {
+        Ok(caps[1].trim().to_string())
+    } else {
+        Err(RunError::AnswerRegexFailed(level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_kinds() {
+        let kinds = [
+            (RunError::BadRequest(404), report::ErrorKind::NotUnlocked),
+            (RunError::BadRequest(500), report::ErrorKind::Network),
+            (RunError::RegexFailed, report::ErrorKind::ExampleNotFound),
+            (
+                RunError::AnswerRegexFailed(2),
+                report::ErrorKind::ExampleNotFound,
+            ),
+            (
+                RunError::StdoutWriteFailed(io::Error::other("closed")),
+                report::ErrorKind::Io,
+            ),
+        ];
+        for (error, kind) in kinds {
+            assert_eq!(error.kind(), kind, "{error}");
+        }
+    }
+}
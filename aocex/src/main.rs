@@ -3,7 +3,7 @@ use std::process;
 fn main() {
     if let Err(e) = aocex::run(Config::make()) {
         eprintln!("ERROR: {}", e);
-        process::exit(1);
+        process::exit(e.kind().exit_code());
     } else {
         process::exit(0);
     }
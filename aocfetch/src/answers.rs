@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+extern crate thiserror;
+use thiserror::Error;
+
+extern crate dirs;
+
+/// error encountered while loading or saving known correct answers
+#[derive(Error, Debug)]
+pub enum AnswerError {
+    #[error("unable to find a data directory for this platform")]
+    NoDataDir,
+    #[error("unable to read answers from {0}: {1}")]
+    ReadError(PathBuf, io::Error),
+    #[error("line {1} of {0} isn't a valid answer record")]
+    MalformedLine(PathBuf, usize),
+    #[error("unable to create data directory {0}: {1}")]
+    DirCreationError(PathBuf, io::Error),
+    #[error("unable to write answers to {0}: {1}")]
+    WriteError(PathBuf, io::Error),
+}
+
+/// known correct answers for each (year, day, level), kept in a tab separated file
+pub struct AnswerStore {
+    path: PathBuf,
+    answers: BTreeMap<(u16, u8, u8), String>,
+}
+
+impl AnswerStore {
+    /// the default location of the answers file (~/.local/share/aoc_utils/answers.tsv on linux)
+    /// each named account has its own answers in ~/.local/share/aoc_utils/accounts/ACCOUNT/answers.tsv
+    pub fn default_path(account: Option<&str>) -> Option<PathBuf> {
+        let mut path = dirs::data_dir()?;
+        path.push("aoc_utils");
+        if let Some(account) = account {
+            path.push("accounts");
+            path.push(account);
+        }
+        path.push("answers.tsv");
+        Some(path)
+    }
+
+    /// load the answers for an account from the default location
+    pub fn open(account: Option<&str>) -> Result<Self, AnswerError> {
+        AnswerStore::open_at(AnswerStore::default_path(account).ok_or(AnswerError::NoDataDir)?)
+    }
+
+    /// load the answers from the given file, which doesn't have to exist yet
+    pub fn open_at(path: PathBuf) -> Result<Self, AnswerError> {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(AnswerError::ReadError(path, e)),
+        };
+
+        // each line is year, day, level and answer separated by tabs
+        let mut answers = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.splitn(4, '\t').collect();
+            let parsed = match fields[..] {
+                [year, day, level, answer] => year
+                    .parse()
+                    .ok()
+                    .zip(day.parse().ok())
+                    .zip(level.parse().ok())
+                    .map(|((year, day), level)| ((year, day, level), answer.to_string())),
+                _ => None,
+            };
+            let (key, answer) =
+                parsed.ok_or_else(|| AnswerError::MalformedLine(path.clone(), i + 1))?;
+            answers.insert(key, answer);
+        }
+
+        Ok(AnswerStore { path, answers })
+    }
+
+    /// the file the answers are loaded from and saved to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// the known correct answer for a puzzle
+    pub fn get(&self, year: u16, day: u8, level: u8) -> Option<&str> {
+        self.answers.get(&(year, day, level)).map(String::as_str)
+    }
+
+    /// record the correct answer for a puzzle, replacing any previous one (call `save` to keep it)
+    pub fn insert(&mut self, year: u16, day: u8, level: u8, answer: &str) {
+        self.answers
+            .insert((year, day, level), answer.trim().to_string());
+    }
+
+    /// every known answer in order of year, day and level
+    pub fn iter(&self) -> impl Iterator<Item = ((u16, u8, u8), &str)> + '_ {
+        self.answers
+            .iter()
+            .map(|(&key, answer)| (key, answer.as_str()))
+    }
+
+    /// write the answers back to the file they were loaded from
+    pub fn save(&self) -> Result<(), AnswerError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| AnswerError::DirCreationError(dir.to_path_buf(), e))?;
+        }
+        let contents: String = self
+            .answers
+            .iter()
+            .map(|((year, day, level), answer)| format!("{year}\t{day}\t{level}\t{answer}\n"))
+            .collect();
+        fs::write(&self.path, contents).map_err(|e| AnswerError::WriteError(self.path.clone(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate tempfile;
+
+    #[test]
+    fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("accounts/alt/answers.tsv");
+
+        let mut store = AnswerStore::open_at(path.clone()).unwrap();
+        assert_eq!(store.iter().count(), 0);
+        store.insert(2019, 2, 1, "3500\n");
+        store.insert(2015, 25, 1, "with\ttabs and spaces");
+        store.insert(2019, 1, 2, "wrong");
+        store.insert(2019, 1, 2, "5");
+        store.save().unwrap();
+
+        let store = AnswerStore::open_at(path.clone()).unwrap();
+        assert_eq!(store.path(), path);
+        assert_eq!(store.get(2019, 2, 1), Some("3500"));
+        assert_eq!(store.get(2019, 1, 2), Some("5"));
+        assert_eq!(store.get(2019, 2, 2), None);
+        assert_eq!(
+            store.iter().collect::<Vec<_>>(),
+            vec![
+                ((2015, 25, 1), "with\ttabs and spaces"),
+                ((2019, 1, 2), "5"),
+                ((2019, 2, 1), "3500"),
+            ]
+        );
+    }
+
+    #[test]
+    fn blank_and_malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("answers.tsv");
+
+        fs::write(&path, "2020\t1\t1\t514579\n\n  \n2020\t1\t2\t241861950\n").unwrap();
+        let store = AnswerStore::open_at(path.clone()).unwrap();
+        assert_eq!(store.iter().count(), 2);
+
+        fs::write(&path, "2020\t1\t1\t514579\n2020\tone\t2\t241861950\n").unwrap();
+        match AnswerStore::open_at(path.clone()) {
+            Err(AnswerError::MalformedLine(p, 2)) => assert_eq!(p, path),
+            other => panic!("expected a malformed line 2, got {:?}", other.err()),
+        }
+
+        fs::write(&path, "2020\t1\t1\n").unwrap();
+        assert!(matches!(
+            AnswerStore::open_at(path),
+            Err(AnswerError::MalformedLine(_, 1))
+        ));
+    }
+
+    #[test]
+    fn account_paths() {
+        let (Some(default), Some(alt)) = (
+            AnswerStore::default_path(None),
+            AnswerStore::default_path(Some("alt")),
+        ) else {
+            return;
+        };
+        assert!(default.ends_with("aoc_utils/answers.tsv"));
+        assert!(alt.ends_with("aoc_utils/accounts/alt/answers.tsv"));
+    }
+}
@@ -0,0 +1,55 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+extern crate thiserror;
+use thiserror::Error;
+
+extern crate dirs;
+
+/// error encountered while saving an input to the cache
+#[derive(Error, Debug)]
+pub enum CacheError {
+    #[error("unable to find a cache directory for this platform")]
+    NoCacheDir,
+    #[error("unable to create cache directory {0}: {1}")]
+    DirCreationError(PathBuf, io::Error),
+    #[error("unable to write cached input to {0}: {1}")]
+    WriteError(PathBuf, io::Error),
+}
+
+/// where the input for a given day is cached (~/.cache/aoc_utils/inputs/YEAR/DAY.txt on linux)
+/// each named account has its own inputs in ~/.cache/aoc_utils/accounts/ACCOUNT/inputs/
+pub fn input_path(account: Option<&str>, year: u16, day: u8) -> Option<PathBuf> {
+    let mut path = dirs::cache_dir()?;
+    path.push("aoc_utils");
+    if let Some(account) = account {
+        path.push("accounts");
+        path.push(account);
+    }
+    path.push("inputs");
+    path.push(year.to_string());
+    path.push(format!("{day}.txt"));
+    Some(path)
+}
+
+/// get the cached input for a given day if we've downloaded it before
+pub fn load(account: Option<&str>, year: u16, day: u8) -> Option<String> {
+    fs::read_to_string(input_path(account, year, day)?).ok()
+}
+
+/// cache the input for a given day so it doesn't have to be requested again
+/// returns where it was cached
+pub fn store(
+    account: Option<&str>,
+    year: u16,
+    day: u8,
+    input: &str,
+) -> Result<PathBuf, CacheError> {
+    let path = input_path(account, year, day).ok_or(CacheError::NoCacheDir)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| CacheError::DirCreationError(dir.to_path_buf(), e))?;
+    }
+    fs::write(&path, input).map_err(|e| CacheError::WriteError(path.clone(), e))?;
+    Ok(path)
+}
@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+extern crate thiserror;
+use thiserror::Error;
+
+extern crate dirs;
+
+/// error encountered while loading or saving when inputs were first fetched
+#[derive(Error, Debug)]
+pub enum FetchLogError {
+    #[error("unable to find a data directory for this platform")]
+    NoDataDir,
+    #[error("unable to read first fetches from {0}: {1}")]
+    ReadError(PathBuf, io::Error),
+    #[error("line {1} of {0} isn't a valid first fetch record")]
+    MalformedLine(PathBuf, usize),
+    #[error("unable to create data directory {0}: {1}")]
+    DirCreationError(PathBuf, io::Error),
+    #[error("unable to write first fetches to {0}: {1}")]
+    WriteError(PathBuf, io::Error),
+}
+
+/// when an input was first downloaded, and which user it was downloaded for
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fetch {
+    /// a unix timestamp
+    pub ts: i64,
+    /// the user's name as adventofcode.com shows it, which is how they're named on leaderboards too
+    pub user: String,
+}
+
+/// when each of an account's inputs was first downloaded, kept in a tab separated file
+/// adventofcode.com doesn't say when someone opened a puzzle, so this is the closest thing to when they started it
+pub struct FetchLog {
+    path: PathBuf,
+    fetches: BTreeMap<(u16, u8), Fetch>,
+}
+
+impl FetchLog {
+    /// the folder every account's data is kept in (~/.local/share/aoc_utils on linux)
+    fn data_dir() -> Option<PathBuf> {
+        let mut path = dirs::data_dir()?;
+        path.push("aoc_utils");
+        Some(path)
+    }
+
+    /// the default location of the first fetches file (~/.local/share/aoc_utils/first_fetches.tsv on linux)
+    /// each named account has its own in ~/.local/share/aoc_utils/accounts/ACCOUNT/first_fetches.tsv
+    pub fn default_path(account: Option<&str>) -> Option<PathBuf> {
+        let mut path = FetchLog::data_dir()?;
+        if let Some(account) = account {
+            path.push("accounts");
+            path.push(account);
+        }
+        path.push("first_fetches.tsv");
+        Some(path)
+    }
+
+    /// load the first fetches for an account from the default location
+    pub fn open(account: Option<&str>) -> Result<Self, FetchLogError> {
+        FetchLog::open_at(FetchLog::default_path(account).ok_or(FetchLogError::NoDataDir)?)
+    }
+
+    /// load the first fetches of every account that has any, named or not
+    pub fn open_all() -> Result<Vec<Self>, FetchLogError> {
+        FetchLog::open_all_in(&FetchLog::data_dir().ok_or(FetchLogError::NoDataDir)?)
+    }
+
+    /// load every first fetches file in the data folder, the unnamed account's first
+    fn open_all_in(dir: &Path) -> Result<Vec<Self>, FetchLogError> {
+        let mut paths = vec![dir.join("first_fetches.tsv")];
+        let accounts = dir.join("accounts");
+        match fs::read_dir(&accounts) {
+            Ok(entries) => {
+                for entry in entries {
+                    let entry = entry.map_err(|e| FetchLogError::ReadError(accounts.clone(), e))?;
+                    paths.push(entry.path().join("first_fetches.tsv"));
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+            Err(e) => return Err(FetchLogError::ReadError(accounts, e)),
+        }
+        paths[1..].sort();
+
+        paths
+            .into_iter()
+            .filter(|path| path.is_file())
+            .map(FetchLog::open_at)
+            .collect()
+    }
+
+    /// load the first fetches from the given file, which doesn't have to exist yet
+    pub fn open_at(path: PathBuf) -> Result<Self, FetchLogError> {
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(FetchLogError::ReadError(path, e)),
+        };
+
+        // each line is year, day, timestamp and user separated by tabs
+        let mut fetches = BTreeMap::new();
+        for (i, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.splitn(4, '\t').collect();
+            let parsed = match fields[..] {
+                [year, day, ts, user] => year
+                    .parse()
+                    .ok()
+                    .zip(day.parse().ok())
+                    .zip(ts.parse().ok())
+                    .map(|((year, day), ts)| {
+                        let user = user.to_string();
+                        ((year, day), Fetch { ts, user })
+                    }),
+                _ => None,
+            };
+            let (key, fetch) =
+                parsed.ok_or_else(|| FetchLogError::MalformedLine(path.clone(), i + 1))?;
+            fetches.insert(key, fetch);
+        }
+
+        Ok(FetchLog { path, fetches })
+    }
+
+    /// the file the first fetches are loaded from and saved to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// when the input for a day was first fetched
+    pub fn get(&self, year: u16, day: u8) -> Option<&Fetch> {
+        self.fetches.get(&(year, day))
+    }
+
+    /// record that the input for a day was fetched, unless it had already been fetched before
+    /// returns whether it was recorded (call `save` to keep it)
+    pub fn record(&mut self, year: u16, day: u8, ts: i64, user: &str) -> bool {
+        if self.fetches.contains_key(&(year, day)) {
+            return false;
+        }
+        let user = user.trim().to_string();
+        self.fetches.insert((year, day), Fetch { ts, user });
+        true
+    }
+
+    /// every first fetch in order of year and day
+    pub fn iter(&self) -> impl Iterator<Item = ((u16, u8), &Fetch)> + '_ {
+        self.fetches.iter().map(|(&key, fetch)| (key, fetch))
+    }
+
+    /// write the first fetches back to the file they were loaded from
+    pub fn save(&self) -> Result<(), FetchLogError> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| FetchLogError::DirCreationError(dir.to_path_buf(), e))?;
+        }
+        let contents: String = self
+            .fetches
+            .iter()
+            .map(|((year, day), fetch)| format!("{year}\t{day}\t{}\t{}\n", fetch.ts, fetch.user))
+            .collect();
+        fs::write(&self.path, contents).map_err(|e| FetchLogError::WriteError(self.path.clone(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate tempfile;
+
+    #[test]
+    fn only_the_first_fetch_is_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("first_fetches.tsv");
+
+        let mut log = FetchLog::open_at(path.clone()).unwrap();
+        assert!(log.record(2023, 5, 1701752400, "(anonymous user #123)"));
+        assert!(log.record(2023, 1, 1701406800, "someone\n"));
+        // fetching again, e.g. with --refresh, doesn't move the start time
+        assert!(!log.record(2023, 5, 1701800000, "someone"));
+        log.save().unwrap();
+
+        let log = FetchLog::open_at(path.clone()).unwrap();
+        assert_eq!(log.path(), path);
+        assert_eq!(
+            log.get(2023, 5),
+            Some(&Fetch {
+                ts: 1701752400,
+                user: "(anonymous user #123)".to_string()
+            })
+        );
+        assert_eq!(log.get(2023, 2), None);
+        assert_eq!(
+            log.iter().map(|(key, _)| key).collect::<Vec<_>>(),
+            vec![(2023, 1), (2023, 5)]
+        );
+        assert_eq!(log.get(2023, 1).unwrap().user, "someone");
+    }
+
+    #[test]
+    fn malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("first_fetches.tsv");
+
+        fs::write(
+            &path,
+            "2023\t1\t1701406800\tsomeone\n\n2023\t2\tnoon\tsomeone\n",
+        )
+        .unwrap();
+        match FetchLog::open_at(path.clone()) {
+            Err(FetchLogError::MalformedLine(p, 3)) => assert_eq!(p, path),
+            other => panic!("expected a malformed line 3, got {:?}", other.err()),
+        }
+
+        fs::write(&path, "2023\t1\t1701406800\n").unwrap();
+        assert!(matches!(
+            FetchLog::open_at(path),
+            Err(FetchLogError::MalformedLine(_, 1))
+        ));
+    }
+
+    #[test]
+    fn every_account() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(FetchLog::open_all_in(dir.path()).unwrap().is_empty());
+
+        for account in [None, Some("work"), Some("alt")] {
+            let mut path = dir.path().to_path_buf();
+            if let Some(account) = account {
+                path.push("accounts");
+                path.push(account);
+            }
+            let mut log = FetchLog::open_at(path.join("first_fetches.tsv")).unwrap();
+            log.record(2023, 1, 1701406800, account.unwrap_or("me"));
+            log.save().unwrap();
+        }
+        // an account that has never fetched anything
+        fs::create_dir_all(dir.path().join("accounts/empty")).unwrap();
+
+        let users: Vec<String> = FetchLog::open_all_in(dir.path())
+            .unwrap()
+            .iter()
+            .map(|log| log.get(2023, 1).unwrap().user.clone())
+            .collect();
+        assert_eq!(users, vec!["me", "alt", "work"]);
+    }
+}
@@ -4,7 +4,7 @@ use std::path::PathBuf;
 
 extern crate clap;
 use clap::error::ErrorKind;
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, Parser, Subcommand};
 
 extern crate chrono;
 use chrono::{DateTime, Datelike, FixedOffset, Utc};
@@ -15,11 +15,23 @@ use thiserror::Error;
 extern crate dirs;
 
 pub mod session;
-use session::SessionError;
+use session::{Keyring, SecretServiceKeyring, SessionArgs, SessionConfig, SessionError};
 
-mod request;
+pub mod answers;
+
+pub mod cache;
+use cache::CacheError;
+
+pub mod fetches;
+use fetches::{FetchLog, FetchLogError};
+
+pub mod request;
 use request::RequestError;
 
+// the report types live in their own crate so aocex can use them without the rest of aocfetch
+pub extern crate aoc_report as report;
+use report::{Puzzle, Report};
+
 #[derive(Parser)]
 #[command(name = "aocfetch")]
 #[command(author = "Daniel Gysi <danielgysi@protonmail.com>")]
@@ -28,16 +40,11 @@ use request::RequestError;
     about = "A command line utility to download puzzle inputs for Advent of Code <https://adventofcode.com>"
 )]
 struct Args {
-    /// your adventofcode.com session cookie
-    #[arg(group = "session", short, long)]
-    cookie: Option<String>,
-    /// a file containing your adventofcode.com session cookie
-    #[arg(group = "session", short, long)]
-    file: Option<PathBuf>,
-    /// the location of your firefox dotfiles (defaults to ~/.mozilla/firefox)
-    // because of the mutual exclusivity with the other session args, we'll handle the default in Config::make
-    #[arg(group = "session", short, long)]
-    browser_folder: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    session: SessionArgs,
 
     /// the day to download the input for
     /// (defaults to current day if UTC-5 is December, otherwise 1)
@@ -53,21 +60,57 @@ struct Args {
     /// (defaults to stdout)
     #[arg(short, long)]
     output: Option<PathBuf>,
+
+    /// download the input again even if it's cached, replacing the cached copy
+    #[arg(long)]
+    refresh: bool,
+
+    /// report where the session cookie came from on stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// print a json object describing what happened instead of plain text
+    #[arg(long, global = true)]
+    json: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// manage the session cookie
+    Session {
+        #[command(subcommand)]
+        command: SessionCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionCommand {
+    /// check that the session cookie is logged in, and show who it's logged in as and when it expires
+    Check,
+    /// store the session cookie in the keyring, where it'll be found by default from then on
+    /// (it's read from stdin unless one of the session args is given)
+    Login,
 }
 
 /// configuration options for the app created based on cli args
 pub struct Config {
+    action: Action,
     session_cfg: SessionConfig,
+    /// the named account, or None for the unnamed one
+    account: Option<String>,
+    verbose: bool,
+    json: bool,
     output_cfg: OutputConfig,
+    refresh: bool,
     day: u8,
     year: u16,
 }
 
-/// keep track of how the application will get the session cookie, inferred from the cli args
-enum SessionConfig {
-    Direct(String),
-    File(PathBuf),
-    Firefox(PathBuf),
+/// keep track of what the application will do
+enum Action {
+    Fetch,
+    CheckSession,
+    Login,
 }
 
 /// keep track of how the application will output the data received
@@ -81,23 +124,6 @@ impl Config {
     pub fn make() -> Self {
         let args = Args::parse();
 
-        // how will we get the session cookie?
-        let session_cfg = if let Some(session_string) = args.cookie {
-            // the user passed it directly
-            SessionConfig::Direct(session_string.clone())
-        } else if let Some(session_file) = args.file {
-            // the user stored it in a file
-            SessionConfig::File(session_file.clone())
-        } else if let Some(firefox_folder) = args.browser_folder {
-            // the user wants to grab it from firefox and provided the config folder
-            SessionConfig::Firefox(firefox_folder.clone())
-        } else {
-            // we default to grabbing it from where we assume the firefox config folder is
-            let mut firefox_folder = dirs::home_dir().unwrap();
-            firefox_folder.push(".mozilla/firefox");
-            SessionConfig::Firefox(firefox_folder)
-        };
-
         // where will we store the output of the request if we get a 200 response
         let output_cfg = if let Some(out_file) = args.output {
             OutputConfig::File(out_file)
@@ -146,15 +172,63 @@ impl Config {
             1
         };
 
+        let action = match args.command {
+            Some(Command::Session {
+                command: SessionCommand::Check,
+            }) => Action::CheckSession,
+            Some(Command::Session {
+                command: SessionCommand::Login,
+            }) => Action::Login,
+            None => Action::Fetch,
+        };
+
+        // how will we get the session cookie?
+        let account = args.session.account();
+        let session_cfg = match (args.session.given_or_env(), &action) {
+            (Some(session_cfg), _) => session_cfg,
+            // logging in without any of the session args means the cookie gets pasted in,
+            // which keeps it out of the shell history
+            (None, Action::Login) => {
+                eprint!("paste your adventofcode.com session cookie: ");
+                let mut buf = String::new();
+                io::stdin().read_line(&mut buf).unwrap_or_else(|_| {
+                    let mut cmd = Args::command();
+                    cmd.error(
+                        ErrorKind::InvalidValue,
+                        "no session cookie provided and it could not be read from stdin",
+                    )
+                    .exit();
+                });
+                SessionConfig::Direct(buf.trim().to_string())
+            }
+            (None, _) => SessionConfig::for_account(account.as_deref()),
+        };
+
         Config {
+            action,
             session_cfg,
+            account,
+            verbose: args.verbose,
+            json: args.json,
             output_cfg,
+            refresh: args.refresh,
             day,
             year,
         }
     }
 }
 
+impl Config {
+    /// figure out the session cookie, reporting where it really came from if asked to
+    fn session_cookie(&self) -> Result<String, SessionError> {
+        let session = self.session_cfg.resolve_with(&SecretServiceKeyring)?;
+        if self.verbose {
+            eprintln!("using the session cookie from {}", session.source);
+        }
+        Ok(session.cookie)
+    }
+}
+
 /// return a DateTime struct representing the current time for AOC
 pub fn get_aoc_time() -> DateTime<Utc> {
     // seconds in an hour
@@ -172,8 +246,12 @@ pub fn get_aoc_time() -> DateTime<Utc> {
 pub enum RunError {
     #[error("error retrieving session cookie: {0}")]
     SessionError(#[from] SessionError),
-    #[error("error occurred while requesting input from adventofcode.com: {0}")]
+    #[error("error occurred while making a request to adventofcode.com: {0}")]
     RequestError(#[from] RequestError),
+    #[error("error occured while caching the input: {0}")]
+    CacheError(#[from] CacheError),
+    #[error("error occured while recording when the input was first fetched: {0}")]
+    FetchLogError(#[from] FetchLogError),
     #[error("error occured while attempting to write to stdout: {0}")]
     StdoutError(io::Error),
     #[error("error occured while attempting to create {0}: {1}")]
@@ -182,31 +260,195 @@ pub enum RunError {
     FileWriteError(PathBuf, io::Error),
 }
 
+impl RunError {
+    /// what kind of problem this is, for --json
+    pub fn kind(&self) -> report::ErrorKind {
+        match self {
+            RunError::SessionError(e) => e.kind(),
+            RunError::RequestError(e) => e.kind(),
+            RunError::CacheError(_)
+            | RunError::FetchLogError(_)
+            | RunError::StdoutError(_)
+            | RunError::FileCreationError(_, _)
+            | RunError::FileWriteError(_, _) => report::ErrorKind::Io,
+        }
+    }
+}
+
 /// run the application according to the provided config
 pub fn run(cfg: Config) -> Result<(), RunError> {
-    // figure out the session cookie
-    let session_cookie = match cfg.session_cfg {
-        SessionConfig::Direct(session_string) => session_string,
-        SessionConfig::File(file) => session::from_file(file)?,
-        SessionConfig::Firefox(folder) => session::from_firefox(folder)?,
+    let mut report = match cfg.action {
+        Action::Fetch => {
+            let puzzle = Puzzle {
+                year: cfg.year,
+                day: Some(cfg.day),
+                level: None,
+            };
+            Report::new("fetch", Some(puzzle), cfg.account.clone())
+        }
+        Action::CheckSession => Report::new("check_session", None, cfg.account.clone()),
+        Action::Login => Report::new("login", None, cfg.account.clone()),
     };
 
-    let recv = request::request_input(cfg.year, cfg.day, &session_cookie)?;
+    // everything meant for stdout is collected here so it can go in the report instead
+    let mut out = String::new();
+    let result = match cfg.action {
+        Action::Fetch => fetch(&cfg, &mut out, &mut report),
+        Action::CheckSession => check_session(&cfg, &mut out),
+        Action::Login => login(&cfg, &mut out),
+    };
 
-    // write to output as determined by the config
-    match cfg.output_cfg {
-        OutputConfig::Stdout => {
-            io::stdout()
-                .write_all(recv.as_bytes())
-                .map_err(RunError::StdoutError)?;
+    if cfg.json {
+        report.finish(
+            out,
+            result.as_ref().err().map(|e| (e.kind(), e.to_string())),
+        );
+        out = report.to_json();
+        out.push('\n');
+    }
+    io::stdout()
+        .write_all(out.as_bytes())
+        .map_err(RunError::StdoutError)?;
+
+    result
+}
+
+/// get the input from the cache or adventofcode.com and write it where the config says to
+fn fetch(cfg: &Config, out: &mut String, report: &mut Report) -> Result<(), RunError> {
+    // stdout is for the input, so the account goes to stderr (or in the report)
+    let account = cfg.account.as_deref();
+    if let (Some(account), false) = (account, cfg.json) {
+        eprintln!("account {account}");
+    }
+
+    // inputs never change, so only ask adventofcode.com for ones we haven't downloaded before
+    // unless a refresh is asked for, e.g. if the cached copy got damaged
+    let cached = if cfg.refresh {
+        None
+    } else {
+        cache::load(account, cfg.year, cfg.day)
+    };
+    let recv = if let Some(cached) = cached {
+        cached
+    } else {
+        let mut fetch_log = FetchLog::open(account)?;
+        let session_cookie = cfg.session_cookie()?;
+        // an expired cookie gets an unhelpful error code, so make sure it's logged in first
+        let user = request::check_session(&session_cookie)?;
+
+        let recv = request::request_input(cfg.year, cfg.day, &session_cookie)?;
+        report
+            .paths
+            .push(cache::store(account, cfg.year, cfg.day, &recv)?);
+        // aocstat times the fair score from when each input was first fetched
+        if fetch_log.record(cfg.year, cfg.day, Utc::now().timestamp(), &user) {
+            fetch_log.save()?;
+            report.paths.push(fetch_log.path().to_path_buf());
         }
+        recv
+    };
+
+    // write to output as determined by the config
+    match &cfg.output_cfg {
+        OutputConfig::Stdout => out.push_str(&recv),
         OutputConfig::File(file) => {
-            let mut out =
-                File::create(&file).map_err(|e| RunError::FileCreationError(file.clone(), e))?;
-            out.write_all(recv.as_bytes())
+            let mut f =
+                File::create(file).map_err(|e| RunError::FileCreationError(file.clone(), e))?;
+            f.write_all(recv.as_bytes())
                 .map_err(|e| RunError::FileWriteError(file.clone(), e))?;
+            report.paths.push(file.clone());
         }
     }
 
     Ok(())
 }
+
+/// report where the session cookie comes from, when it expires if that's known, and who it's logged in as
+fn check_session(cfg: &Config, out: &mut String) -> Result<(), RunError> {
+    // by default it's only known whether the cookie comes from the keyring or firefox once it's been found
+    let session = cfg.session_cfg.resolve_with(&SecretServiceKeyring)?;
+    if let Some(account) = &cfg.account {
+        out.push_str(&format!("account {account}\n"));
+    }
+    out.push_str(&format!("session cookie from {}\n", session.source));
+
+    if let Some(expiry) = session.expiry()? {
+        let days = (expiry - Utc::now()).num_days();
+        let when = expiry.format("%Y-%m-%d %H:%M:%S UTC");
+        if expiry < Utc::now() {
+            out.push_str(&format!("expired at {when}\n"));
+        } else {
+            out.push_str(&format!("expires at {when} (in {days} days)\n"));
+        }
+    }
+
+    // what we know so far still gets shown if the check fails
+    let user = request::check_session(&session.cookie)?;
+    out.push_str(&format!("logged in as {user}\n"));
+
+    Ok(())
+}
+
+/// make sure the session cookie is logged in, then store it in the keyring
+fn login(cfg: &Config, out: &mut String) -> Result<(), RunError> {
+    let session_cookie = cfg.session_cookie()?;
+    let user = request::check_session(&session_cookie)?;
+    SecretServiceKeyring.store(cfg.account.as_deref(), &session_cookie)?;
+
+    match &cfg.account {
+        Some(account) => out.push_str(&format!(
+            "stored the session cookie for {user} in the keyring as account {account}\n"
+        )),
+        None => out.push_str(&format!(
+            "stored the session cookie for {user} in the keyring\n"
+        )),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_kinds() {
+        let kinds = [
+            (
+                RunError::SessionError(SessionError::MissingCookie),
+                report::ErrorKind::Session,
+            ),
+            (
+                RunError::RequestError(RequestError::LoggedOut),
+                report::ErrorKind::Session,
+            ),
+            (
+                RunError::RequestError(RequestError::BadResponse(404)),
+                report::ErrorKind::NotUnlocked,
+            ),
+            (
+                RunError::RequestError(RequestError::BadResponse(400)),
+                report::ErrorKind::Unauthorized,
+            ),
+            (
+                RunError::RequestError(RequestError::BadResponse(500)),
+                report::ErrorKind::Network,
+            ),
+            (
+                RunError::CacheError(CacheError::NoCacheDir),
+                report::ErrorKind::Io,
+            ),
+            (
+                RunError::FetchLogError(FetchLogError::NoDataDir),
+                report::ErrorKind::Io,
+            ),
+            (
+                RunError::FileWriteError(PathBuf::from("input.txt"), io::Error::other("disk full")),
+                report::ErrorKind::Io,
+            ),
+        ];
+        for (error, kind) in kinds {
+            assert_eq!(error.kind(), kind, "{error}");
+        }
+    }
+}
@@ -3,7 +3,7 @@ use std::process;
 fn main() {
     if let Err(err) = aocfetch::run(Config::make()) {
         eprintln!("{}", err);
-        process::exit(1);
+        process::exit(err.kind().exit_code());
     }
     process::exit(0);
 }
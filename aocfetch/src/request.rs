@@ -5,27 +5,69 @@ extern crate reqwest;
 use reqwest::blocking::Client;
 use reqwest::StatusCode;
 
-/// an error encountered while making the request for input
+use crate::report::ErrorKind;
+
+/// an error encountered while making a request to adventofcode.com
 #[derive(Error, Debug)]
 pub enum RequestError {
     #[error("unable to complete request to {0}: {1}")]
     RequestFailed(String, reqwest::Error),
+    #[error("unable to read the response from {0}: {1}")]
+    UnreadableResponse(String, reqwest::Error),
     #[error("bad response from server with status code {0}")]
     BadResponse(u16),
+    #[error("the session cookie isn't logged in to adventofcode.com, it has probably expired")]
+    LoggedOut,
+}
+
+impl RequestError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            RequestError::RequestFailed(_, _) | RequestError::UnreadableResponse(_, _) => {
+                ErrorKind::Network
+            }
+            RequestError::BadResponse(status) => ErrorKind::from_status(*status),
+            RequestError::LoggedOut => ErrorKind::Session,
+        }
+    }
 }
 
 /// given url params and a cookie, make a request for the day's input and return the text or error
 pub fn request_input(year: u16, day: u8, session_cookie: &str) -> Result<String, RequestError> {
-    let url = format!("https://adventofcode.com/{year}/day/{day}/input");
+    get_page(
+        &format!("https://adventofcode.com/{year}/day/{day}/input"),
+        session_cookie,
+    )
+}
+
+/// given a url and a cookie, make an authenticated get request and return the text or error
+pub fn get_page(url: &str, session_cookie: &str) -> Result<String, RequestError> {
     let client = Client::new();
     let response = client
-        .get(&url)
+        .get(url)
         .header("Cookie", format!("session={session_cookie}"))
         .send()
-        .map_err(|e| RequestError::RequestFailed(url, e))?;
+        .map_err(|e| RequestError::RequestFailed(url.to_string(), e))?;
 
     match response.status() {
-        StatusCode::OK => Ok(response.text().unwrap()),
+        StatusCode::OK => response
+            .text()
+            .map_err(|e| RequestError::UnreadableResponse(url.to_string(), e)),
         other => Err(RequestError::BadResponse(other.as_u16())),
     }
 }
+
+/// make sure the cookie is logged in by fetching the home page, which shows the user's name when it is
+/// returns the name
+pub fn check_session(session_cookie: &str) -> Result<String, RequestError> {
+    let page = get_page("https://adventofcode.com/", session_cookie)?;
+    parse_user(&page).ok_or(RequestError::LoggedOut)
+}
+
+/// given the raw html of a page, find the user's name in the header
+/// (which has a link to log in instead when logged out)
+fn parse_user(page: &str) -> Option<String> {
+    let (_, rest) = page.split_once(r#"<div class="user">"#)?;
+    let (name, _) = rest.split_once('<')?;
+    Some(name.trim().to_string())
+}
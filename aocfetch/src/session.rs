@@ -1,7 +1,15 @@
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
 use std::fs;
 use std::io;
 use std::path::PathBuf;
 
+extern crate clap;
+
+extern crate chrono;
+use chrono::{DateTime, Utc};
+
 extern crate thiserror;
 use thiserror::Error;
 
@@ -11,6 +19,14 @@ use ini::Ini;
 extern crate rusqlite;
 use rusqlite::Connection;
 
+extern crate tempfile;
+
+extern crate secret_service;
+use secret_service::blocking::{Item, SecretService};
+use secret_service::EncryptionType;
+
+use crate::report::ErrorKind;
+
 /// error encountered while retreiving session cookie
 #[derive(Error, Debug)]
 pub enum SessionError {
@@ -26,8 +42,8 @@ pub enum SessionError {
     CantOpenDb(rusqlite::Error),
     #[error("unable to copy cookies database: {0}")]
     CantCopyDb(io::Error),
-    #[error("unable to delete temporary cookies database: {0}")]
-    CantDeleteTempDb(io::Error),
+    #[error("unable to create a temporary directory for the cookies database: {0}")]
+    CantCreateTempDir(io::Error),
     #[error("error preparing statement for cookies database: {0}")]
     StatementPrepError(rusqlite::Error),
     #[error("error executing query on cookies database: {0}")]
@@ -36,6 +52,260 @@ pub enum SessionError {
     RowsError(rusqlite::Error),
     #[error("can't find adventofcode.com session cookie in firefox profile's cookie database")]
     MissingCookie,
+    #[error("the session cookie in firefox's cookie database has an invalid expiry time {0}")]
+    InvalidExpiry(i64),
+    #[error("unable to connect to the keyring through the secret service api: {0}")]
+    KeyringUnavailable(secret_service::Error),
+    #[error("unable to use the keyring through the secret service api: {0}")]
+    KeyringError(secret_service::Error),
+    #[error("no session cookie in the keyring, store one with `{0}`")]
+    MissingKeyringCookie(String),
+    #[error("the session cookie in the keyring isn't valid utf-8")]
+    InvalidKeyringCookie,
+}
+
+impl SessionError {
+    /// every problem getting the cookie is a session problem, wherever it came from
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::Session
+    }
+}
+
+/// the cli args for supplying the session cookie, shared by every app that talks to adventofcode.com
+#[derive(clap::Args)]
+pub struct SessionArgs {
+    /// your adventofcode.com session cookie
+    #[arg(short, long, global = true, conflicts_with_all = ["file", "browser_folder"])]
+    cookie: Option<String>,
+    /// a file containing your adventofcode.com session cookie
+    #[arg(short, long, global = true, conflicts_with = "browser_folder")]
+    file: Option<PathBuf>,
+    /// the location of your firefox dotfiles (defaults to ~/.mozilla/firefox)
+    // because of the mutual exclusivity with the other session args, we'll handle the default in SessionConfig::from
+    #[arg(short, long, global = true)]
+    browser_folder: Option<PathBuf>,
+    /// the named account to use, which has its own cookie in the keyring, cached inputs and recorded answers
+    /// (defaults to the unnamed account)
+    #[arg(long, global = true, value_parser = parse_account)]
+    account: Option<String>,
+}
+
+/// check that an account name is safe to use as a folder name
+pub fn parse_account(name: &str) -> Result<String, String> {
+    if !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        Ok(name.to_string())
+    } else {
+        Err("account names can only contain letters, numbers, - and _".to_string())
+    }
+}
+
+/// keep track of how the application will get the session cookie, inferred from the cli args and environment
+/// in order of precedence: the --cookie, --file and --browser-folder flags, then the AOC_SESSION and AOC_SESSION_FILE
+/// environment variables (only for the unnamed account), then the keyring, then the default firefox profile
+#[derive(Clone)]
+pub enum SessionConfig {
+    Direct(String),
+    File(PathBuf),
+    Firefox(PathBuf),
+    EnvCookie(String),
+    EnvFile(PathBuf),
+    /// the cookie stored for the account, or for the unnamed account if None
+    Keyring(Option<String>),
+    /// the unnamed account's cookie from the keyring if one is stored there, otherwise from the firefox profile
+    /// (the keyring is only checked once the cookie is needed, since that may mean starting a d-bus session)
+    KeyringOrFirefox(PathBuf),
+}
+
+impl Default for SessionConfig {
+    /// grab the cookie from the keyring if `aocfetch session login` has stored one there,
+    /// otherwise from where we assume the firefox config folder is
+    fn default() -> Self {
+        SessionConfig::for_account(None)
+    }
+}
+
+impl SessionConfig {
+    /// where the cookie for the account comes from when none of the session args or environment variables are used
+    /// the firefox profile only holds one cookie, so a named account's cookie has to come from the keyring
+    pub fn for_account(account: Option<&str>) -> Self {
+        if let Some(account) = account {
+            return SessionConfig::Keyring(Some(account.to_string()));
+        }
+        let mut firefox_folder = dirs::home_dir().unwrap();
+        firefox_folder.push(".mozilla/firefox");
+        SessionConfig::KeyringOrFirefox(firefox_folder)
+    }
+}
+
+impl SessionArgs {
+    /// the named account the user asked for, if any
+    pub fn account(&self) -> Option<String> {
+        self.account.clone()
+    }
+
+    /// the session config the user asked for, if they used any of the session args
+    pub fn given(self) -> Option<SessionConfig> {
+        if let Some(session_string) = self.cookie {
+            // the user passed it directly
+            Some(SessionConfig::Direct(session_string))
+        } else if let Some(session_file) = self.file {
+            // the user stored it in a file
+            Some(SessionConfig::File(session_file))
+        } else {
+            // the user wants to grab it from firefox and provided the config folder
+            self.browser_folder.map(SessionConfig::Firefox)
+        }
+    }
+}
+
+impl SessionArgs {
+    /// the session config from the session args, or failing that from the environment variables
+    /// the environment variables only hold one cookie, so they're ignored for a named account rather than
+    /// fetching (and permanently caching) someone else's input under its name
+    pub fn given_or_env(self) -> Option<SessionConfig> {
+        self.given_or_vars(|name| env::var(name).ok())
+    }
+
+    fn given_or_vars(self, var: impl Fn(&str) -> Option<String>) -> Option<SessionConfig> {
+        if self.account.is_some() {
+            self.given()
+        } else {
+            self.given().or_else(|| SessionConfig::from_vars(var))
+        }
+    }
+}
+
+impl SessionConfig {
+    /// the session config from the AOC_SESSION or AOC_SESSION_FILE environment variables, if either is set
+    /// (e.g. for ci jobs and containers that can't reach a firefox profile)
+    pub fn from_env() -> Option<Self> {
+        SessionConfig::from_vars(|name| env::var(name).ok())
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        let var = |name| var(name).filter(|value| !value.trim().is_empty());
+        if let Some(session_string) = var("AOC_SESSION") {
+            Some(SessionConfig::EnvCookie(session_string.trim().to_string()))
+        } else {
+            var("AOC_SESSION_FILE").map(|file| SessionConfig::EnvFile(PathBuf::from(file)))
+        }
+    }
+}
+
+impl From<SessionArgs> for SessionConfig {
+    fn from(args: SessionArgs) -> Self {
+        let account = args.account();
+        args.given_or_env()
+            .unwrap_or_else(|| SessionConfig::for_account(account.as_deref()))
+    }
+}
+
+/// a session cookie along with where it really came from
+pub struct ResolvedSession {
+    pub cookie: String,
+    /// never KeyringOrFirefox, since that's resolved to whichever of the two had the cookie
+    pub source: SessionConfig,
+    /// the expiry time stored next to the cookie in firefox's database, read at the same time as the cookie
+    firefox_expiry: Option<i64>,
+}
+
+impl ResolvedSession {
+    /// when the cookie expires, which is only known for cookies from firefox
+    pub fn expiry(&self) -> Result<Option<DateTime<Utc>>, SessionError> {
+        let Some(expiry) = self.firefox_expiry else {
+            return Ok(None);
+        };
+        // older versions of firefox store the expiry in seconds, newer ones in milliseconds
+        let seconds = if expiry > 100_000_000_000 {
+            expiry / 1000
+        } else {
+            expiry
+        };
+        DateTime::from_timestamp(seconds, 0)
+            .map(Some)
+            .ok_or(SessionError::InvalidExpiry(expiry))
+    }
+}
+
+impl SessionConfig {
+    /// figure out the session cookie
+    pub fn resolve(&self) -> Result<String, SessionError> {
+        Ok(self.resolve_with(&SecretServiceKeyring)?.cookie)
+    }
+
+    /// figure out the session cookie with the given keyring, along with where it really came from
+    /// (which is only known for KeyringOrFirefox once the keyring has been checked)
+    pub fn resolve_with(&self, keyring: &impl Keyring) -> Result<ResolvedSession, SessionError> {
+        let mut firefox_expiry = None;
+        let cookie = match self {
+            SessionConfig::Direct(session_string) | SessionConfig::EnvCookie(session_string) => {
+                session_string.clone()
+            }
+            SessionConfig::File(file) | SessionConfig::EnvFile(file) => from_file(file.clone())?,
+            SessionConfig::Firefox(folder) => {
+                let (cookie, expiry) = from_firefox(folder.clone())?;
+                firefox_expiry = Some(expiry);
+                cookie
+            }
+            SessionConfig::Keyring(account) => {
+                keyring.lookup(account.as_deref())?.ok_or_else(|| {
+                    SessionError::MissingKeyringCookie(match account {
+                        Some(account) => format!("aocfetch session login --account {account}"),
+                        None => "aocfetch session login".to_string(),
+                    })
+                })?
+            }
+            // no keyring counts as no cookie in the keyring, but a keyring that fails to give up its cookie doesn't
+            SessionConfig::KeyringOrFirefox(folder) => {
+                return match keyring.lookup(None) {
+                    Ok(Some(cookie)) => Ok(ResolvedSession {
+                        cookie,
+                        source: SessionConfig::Keyring(None),
+                        firefox_expiry: None,
+                    }),
+                    Ok(None) | Err(SessionError::KeyringUnavailable(_)) => {
+                        SessionConfig::Firefox(folder.clone()).resolve_with(keyring)
+                    }
+                    Err(e) => Err(e),
+                }
+            }
+        };
+        Ok(ResolvedSession {
+            cookie,
+            source: self.clone(),
+            firefox_expiry,
+        })
+    }
+}
+
+impl fmt::Display for SessionConfig {
+    /// where the cookie comes from, without giving the cookie away
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SessionConfig::Direct(_) => write!(f, "the --cookie flag"),
+            SessionConfig::File(file) => write!(f, "the file {}", file.display()),
+            SessionConfig::Firefox(folder) => {
+                write!(f, "the firefox profile in {}", folder.display())
+            }
+            SessionConfig::EnvCookie(_) => write!(f, "the AOC_SESSION environment variable"),
+            SessionConfig::EnvFile(file) => {
+                write!(f, "the file {} (from AOC_SESSION_FILE)", file.display())
+            }
+            SessionConfig::Keyring(None) => write!(f, "the keyring"),
+            SessionConfig::Keyring(Some(account)) => {
+                write!(f, "the keyring (account {account})")
+            }
+            SessionConfig::KeyringOrFirefox(folder) => write!(
+                f,
+                "the keyring, or the firefox profile in {} if there's no cookie in the keyring",
+                folder.display()
+            ),
+        }
+    }
 }
 
 /// pull session cookie from file containing only that
@@ -46,8 +316,9 @@ pub fn from_file(file: PathBuf) -> Result<String, SessionError> {
         .to_string())
 }
 
-/// pull session cookie from user's firefox profile
-pub fn from_firefox(folder: PathBuf) -> Result<String, SessionError> {
+/// pull session cookie and its raw expiry time from user's firefox profile
+/// both come from the same copy of the database, so they always belong together
+pub fn from_firefox(folder: PathBuf) -> Result<(String, i64), SessionError> {
     // get path to profile of interest
     let profile_path = get_profile_path(folder)?;
     // construct the path to the cookies database
@@ -57,6 +328,99 @@ pub fn from_firefox(folder: PathBuf) -> Result<String, SessionError> {
     extract_cookie(cookie_db_path)
 }
 
+/// the attributes the session cookie for an account is stored under in the keyring
+/// the unnamed account's cookie has no account attribute
+fn keyring_attributes(account: Option<&str>) -> HashMap<&str, &str> {
+    let mut attributes = HashMap::from([("application", "aoc_utils"), ("name", "session")]);
+    if let Some(account) = account {
+        attributes.insert("account", account);
+    }
+    attributes
+}
+
+/// whether a keyring item with the attributes belongs to the account
+/// searching for the unnamed account's attributes also finds every named account's cookie, so the account
+/// attribute has to match exactly (including being missing for the unnamed account)
+fn is_account_item(attributes: &HashMap<String, String>, account: Option<&str>) -> bool {
+    attributes.get("account").map(String::as_str) == account
+}
+
+/// the keyring item holding the cookie for an account, if there is one
+fn keyring_item<'a>(
+    ss: &'a SecretService,
+    account: Option<&str>,
+) -> Result<Option<Item<'a>>, SessionError> {
+    let found = ss
+        .search_items(keyring_attributes(account))
+        .map_err(SessionError::KeyringError)?;
+    for item in found.unlocked.into_iter().chain(found.locked) {
+        let attributes = item.get_attributes().map_err(SessionError::KeyringError)?;
+        if is_account_item(&attributes, account) {
+            return Ok(Some(item));
+        }
+    }
+    Ok(None)
+}
+
+/// somewhere to keep a session cookie for each account
+pub trait Keyring {
+    /// the cookie stored for the account, or None if there isn't one
+    fn lookup(&self, account: Option<&str>) -> Result<Option<String>, SessionError>;
+
+    /// store the cookie for the account, replacing any stored before
+    fn store(&self, account: Option<&str>, session_cookie: &str) -> Result<(), SessionError>;
+}
+
+/// the user's keyring (e.g. gnome-keyring or kwallet), through the secret service d-bus api
+pub struct SecretServiceKeyring;
+
+impl SecretServiceKeyring {
+    fn connect() -> Result<SecretService<'static>, SessionError> {
+        SecretService::connect(EncryptionType::Dh).map_err(SessionError::KeyringUnavailable)
+    }
+}
+
+impl Keyring for SecretServiceKeyring {
+    fn lookup(&self, account: Option<&str>) -> Result<Option<String>, SessionError> {
+        let ss = SecretServiceKeyring::connect()?;
+        let Some(item) = keyring_item(&ss, account)? else {
+            return Ok(None);
+        };
+        // this may ask the user to unlock the keyring
+        item.ensure_unlocked().map_err(SessionError::KeyringError)?;
+        let secret = item.get_secret().map_err(SessionError::KeyringError)?;
+        String::from_utf8(secret)
+            .map(Some)
+            .map_err(|_| SessionError::InvalidKeyringCookie)
+    }
+
+    /// the cookie goes in the keyring's default collection
+    fn store(&self, account: Option<&str>, session_cookie: &str) -> Result<(), SessionError> {
+        let ss = SecretServiceKeyring::connect()?;
+        let collection = ss
+            .get_default_collection()
+            .map_err(SessionError::KeyringError)?;
+        // this may ask the user to unlock the keyring
+        collection
+            .ensure_unlocked()
+            .map_err(SessionError::KeyringError)?;
+        let label = match account {
+            Some(account) => format!("Advent of Code session cookie ({account})"),
+            None => "Advent of Code session cookie".to_string(),
+        };
+        collection
+            .create_item(
+                &label,
+                keyring_attributes(account),
+                session_cookie.trim().as_bytes(),
+                true,
+                "text/plain",
+            )
+            .map_err(SessionError::KeyringError)?;
+        Ok(())
+    }
+}
+
 /// given the location of the firefox dotfiles, get the full path to the profile from which we'll extract the cookie
 fn get_profile_path(firefox_path: PathBuf) -> Result<PathBuf, SessionError> {
     const PROFILE_NAME: &str = "default-release"; // this probably shouldn't be a constant but I'm not sure how firefox determines which profile is in use. Maybe this can be an arg.
@@ -88,33 +452,319 @@ fn get_profile_path(firefox_path: PathBuf) -> Result<PathBuf, SessionError> {
     Ok(profile_path)
 }
 
-/// given the path to the cookies database, extract the session cookie if it exists
-fn extract_cookie(dbpath: PathBuf) -> Result<String, SessionError> {
-    const TEMP_FILE: &str = "/tmp/aoc_utils-temp-db.sqlite";
+/// given the path to the cookies database, extract the session cookie and its expiry time if it exists
+fn extract_cookie(dbpath: PathBuf) -> Result<(String, i64), SessionError> {
     const QUERY: &str =
-        "SELECT value FROM moz_cookies WHERE host LIKE '%.adventofcode.com' AND name='session' LIMIT 1;";
+        "SELECT value, expiry FROM moz_cookies WHERE host LIKE '%.adventofcode.com' AND name='session' LIMIT 1;";
 
     // copy the database to a new temp file in case it's locked
     // Ideally we'd check if it's locked first but that's a huge pain in the ass, I've learned.
     // It goes all the way back to libsqlite3 in C. There's no function for it, you just have to prepare
     // a statement and check. wtf.
-    let temp_path = PathBuf::from(TEMP_FILE);
-    fs::copy(dbpath, &temp_path).map_err(SessionError::CantCopyDb)?;
+    // The copy goes in a private directory that's deleted when it's dropped, even if the query fails.
+    // A running firefox keeps recent changes in the write-ahead log until it checkpoints them, so that's
+    // copied too (with a matching name, so sqlite picks it up when the copy is opened).
+    let temp_dir = tempfile::Builder::new()
+        .prefix("aoc_utils-")
+        .tempdir()
+        .map_err(SessionError::CantCreateTempDir)?;
+    let temp_path = temp_dir.path().join("cookies.sqlite");
+    fs::copy(&dbpath, &temp_path).map_err(SessionError::CantCopyDb)?;
+    let mut wal_path = dbpath.into_os_string();
+    wal_path.push("-wal");
+    match fs::copy(&wal_path, temp_dir.path().join("cookies.sqlite-wal")) {
+        Ok(_) => (),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => (),
+        Err(e) => return Err(SessionError::CantCopyDb(e)),
+    }
     let con = Connection::open(&temp_path).map_err(SessionError::CantOpenDb)?;
 
     let mut stmt = con
         .prepare(QUERY)
         .map_err(SessionError::StatementPrepError)?;
 
-    let cookie: String = stmt
-        .query([])
-        .map_err(SessionError::QueryError)?
-        .next()
-        .map_err(SessionError::RowsError)?
-        .ok_or(SessionError::MissingCookie)?
-        .get_unwrap(0);
-
-    fs::remove_file(temp_path).map_err(SessionError::CantDeleteTempDb)?;
+    let cookie: (String, i64) = {
+        let mut rows = stmt.query([]).map_err(SessionError::QueryError)?;
+        let row = rows
+            .next()
+            .map_err(SessionError::RowsError)?
+            .ok_or(SessionError::MissingCookie)?;
+        (row.get_unwrap(0), row.get_unwrap(1))
+    };
 
     Ok(cookie)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+
+    /// a keyring kept in memory, which counts how often it's asked for a cookie
+    #[derive(Default)]
+    struct MemoryKeyring {
+        cookies: RefCell<HashMap<Option<String>, String>>,
+        lookups: Cell<usize>,
+        unavailable: bool,
+    }
+
+    impl MemoryKeyring {
+        /// a keyring that can't be connected to, like on a machine without a d-bus session
+        fn unavailable() -> Self {
+            MemoryKeyring {
+                unavailable: true,
+                ..MemoryKeyring::default()
+            }
+        }
+    }
+
+    impl Keyring for MemoryKeyring {
+        fn lookup(&self, account: Option<&str>) -> Result<Option<String>, SessionError> {
+            self.lookups.set(self.lookups.get() + 1);
+            if self.unavailable {
+                return Err(SessionError::KeyringUnavailable(
+                    secret_service::Error::Unavailable,
+                ));
+            }
+            Ok(self
+                .cookies
+                .borrow()
+                .get(&account.map(str::to_string))
+                .cloned())
+        }
+
+        fn store(&self, account: Option<&str>, session_cookie: &str) -> Result<(), SessionError> {
+            if self.unavailable {
+                return Err(SessionError::KeyringUnavailable(
+                    secret_service::Error::Unavailable,
+                ));
+            }
+            self.cookies
+                .borrow_mut()
+                .insert(account.map(str::to_string), session_cookie.to_string());
+            Ok(())
+        }
+    }
+
+    /// the default config with a firefox folder that doesn't exist, so falling back to it fails to load profiles.ini
+    fn default_config() -> (tempfile::TempDir, SessionConfig) {
+        let dir = tempfile::tempdir().unwrap();
+        let folder = dir.path().join("firefox");
+        (dir, SessionConfig::KeyringOrFirefox(folder))
+    }
+
+    #[test]
+    fn named_account_cookie() {
+        let keyring = MemoryKeyring::default();
+        keyring.store(None, "unnamed").unwrap();
+        keyring.store(Some("alt"), "alt").unwrap();
+        let session = SessionConfig::for_account(Some("alt"))
+            .resolve_with(&keyring)
+            .unwrap();
+        assert_eq!(session.cookie, "alt");
+        assert_eq!(session.source.to_string(), "the keyring (account alt)");
+        assert_eq!(session.expiry().unwrap(), None);
+    }
+
+    #[test]
+    fn missing_account_cookie() {
+        let keyring = MemoryKeyring::default();
+        keyring.store(None, "unnamed").unwrap();
+        match SessionConfig::for_account(Some("alt")).resolve_with(&keyring) {
+            Err(SessionError::MissingKeyringCookie(command)) => {
+                assert_eq!(command, "aocfetch session login --account alt")
+            }
+            other => panic!("expected a missing cookie, got {:?}", other.err()),
+        }
+        // a named account never falls back to firefox, which only holds one cookie
+        let keyring = MemoryKeyring::unavailable();
+        assert!(matches!(
+            SessionConfig::for_account(Some("alt")).resolve_with(&keyring),
+            Err(SessionError::KeyringUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn keyring_before_firefox() {
+        let (_dir, config) = default_config();
+        let keyring = MemoryKeyring::default();
+        keyring.store(None, "from the keyring").unwrap();
+        let session = config.resolve_with(&keyring).unwrap();
+        assert_eq!(session.cookie, "from the keyring");
+        assert_eq!(session.source.to_string(), "the keyring");
+        assert_eq!(session.expiry().unwrap(), None);
+    }
+
+    #[test]
+    fn firefox_without_keyring_cookie() {
+        let (_dir, config) = default_config();
+        // only a named account's cookie is stored, which doesn't count for the unnamed account
+        let keyring = MemoryKeyring::default();
+        keyring.store(Some("alt"), "someone else's").unwrap();
+        assert!(matches!(
+            config.resolve_with(&keyring),
+            Err(SessionError::IniLoadError(_))
+        ));
+        // no keyring at all counts as no cookie in it
+        let keyring = MemoryKeyring::unavailable();
+        assert!(matches!(
+            config.resolve_with(&keyring),
+            Err(SessionError::IniLoadError(_))
+        ));
+    }
+
+    /// a firefox folder with a default-release profile whose cookies database holds the session cookie
+    fn firefox_folder(dir: &tempfile::TempDir, cookie: &str, expiry: i64) -> PathBuf {
+        let folder = dir.path().join("firefox");
+        let profile = folder.join("abc.default-release");
+        fs::create_dir_all(&profile).unwrap();
+        fs::write(
+            folder.join("profiles.ini"),
+            "[Profile0]\nName=default-release\nPath=abc.default-release\n",
+        )
+        .unwrap();
+        let con = Connection::open(profile.join("cookies.sqlite")).unwrap();
+        con.execute_batch(
+            "CREATE TABLE moz_cookies (name TEXT, value TEXT, host TEXT, expiry INTEGER);
+             INSERT INTO moz_cookies VALUES ('other', 'nope', '.example.com', 1);",
+        )
+        .unwrap();
+        con.execute(
+            "INSERT INTO moz_cookies VALUES ('session', ?1, '.adventofcode.com', ?2)",
+            (cookie, expiry),
+        )
+        .unwrap();
+        folder
+    }
+
+    #[test]
+    fn firefox_cookie_and_expiry() {
+        let dir = tempfile::tempdir().unwrap();
+        // newer versions of firefox store the expiry in milliseconds
+        let folder = firefox_folder(&dir, "from firefox", 1_767_225_600_000);
+        let keyring = MemoryKeyring::default();
+        let session = SessionConfig::KeyringOrFirefox(folder.clone())
+            .resolve_with(&keyring)
+            .unwrap();
+        assert_eq!(session.cookie, "from firefox");
+        assert!(matches!(&session.source, SessionConfig::Firefox(f) if *f == folder));
+        assert_eq!(
+            session.expiry().unwrap().map(|e| e.timestamp()),
+            Some(1_767_225_600)
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let folder = firefox_folder(&dir, "from firefox", i64::MAX);
+        let session = SessionConfig::Firefox(folder)
+            .resolve_with(&keyring)
+            .unwrap();
+        assert!(matches!(
+            session.expiry(),
+            Err(SessionError::InvalidExpiry(i64::MAX))
+        ));
+    }
+
+    #[test]
+    fn keyring_only_checked_when_needed() {
+        let keyring = MemoryKeyring::default();
+        keyring.store(None, "from the keyring").unwrap();
+        let session = SessionConfig::Direct("from the flag".to_string())
+            .resolve_with(&keyring)
+            .unwrap();
+        assert_eq!(session.cookie, "from the flag");
+        let session = SessionConfig::EnvCookie("from the environment".to_string())
+            .resolve_with(&keyring)
+            .unwrap();
+        assert_eq!(session.cookie, "from the environment");
+        assert_eq!(keyring.lookups.get(), 0);
+    }
+
+    /// session args with only the cookie flag and the account set
+    fn args(cookie: Option<&str>, account: Option<&str>) -> SessionArgs {
+        SessionArgs {
+            cookie: cookie.map(str::to_string),
+            file: None,
+            browser_folder: None,
+            account: account.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn environment_only_for_unnamed_account() {
+        let env =
+            |name: &str| (name == "AOC_SESSION").then(|| " from the environment\n".to_string());
+        let source = |args: SessionArgs| args.given_or_vars(env).map(|cfg| cfg.to_string());
+
+        assert_eq!(
+            source(args(None, None)).as_deref(),
+            Some("the AOC_SESSION environment variable")
+        );
+        assert_eq!(
+            source(args(Some("flag"), None)).as_deref(),
+            Some("the --cookie flag")
+        );
+        // a named account goes to the keyring instead of using the environment's cookie
+        assert_eq!(source(args(None, Some("work"))), None);
+        assert_eq!(
+            source(args(Some("flag"), Some("work"))).as_deref(),
+            Some("the --cookie flag")
+        );
+
+        let file = |name: &str| (name == "AOC_SESSION_FILE").then(|| "cookie.txt".to_string());
+        assert!(matches!(
+            args(None, None).given_or_vars(file),
+            Some(SessionConfig::EnvFile(f)) if f.as_os_str() == "cookie.txt"
+        ));
+        assert!(args(None, Some("work")).given_or_vars(file).is_none());
+        assert!(args(None, None)
+            .given_or_vars(|_| Some(" ".to_string()))
+            .is_none());
+    }
+
+    #[test]
+    fn account_items() {
+        let unnamed = HashMap::from([("application".to_string(), "aoc_utils".to_string())]);
+        let mut named = unnamed.clone();
+        named.insert("account".to_string(), "alt".to_string());
+        assert!(is_account_item(&unnamed, None));
+        assert!(!is_account_item(&unnamed, Some("alt")));
+        assert!(is_account_item(&named, Some("alt")));
+        assert!(!is_account_item(&named, None));
+        assert!(!is_account_item(&named, Some("other")));
+    }
+
+    /// store and look up cookies in a real secret service, then delete them again
+    /// it's ignored by default since it needs a session bus with an unlocked keyring, e.g. run it with
+    /// `dbus-run-session -- sh -c 'echo -n test | gnome-keyring-daemon --unlock && cargo test -- --ignored'`
+    #[test]
+    #[ignore]
+    fn secret_service_round_trip() {
+        let ss = match SecretServiceKeyring::connect() {
+            Ok(ss) => ss,
+            Err(e) => {
+                eprintln!("skipping, no secret service: {e}");
+                return;
+            }
+        };
+        // an account name no one uses, so nothing real is touched
+        let account = format!("aoc-utils-test-{}", std::process::id());
+        let account = Some(account.as_str());
+
+        assert_eq!(SecretServiceKeyring.lookup(account).unwrap(), None);
+        SecretServiceKeyring.store(account, " first\n").unwrap();
+        SecretServiceKeyring.store(account, "second").unwrap();
+        assert_eq!(
+            SecretServiceKeyring.lookup(account).unwrap().as_deref(),
+            Some("second")
+        );
+        let session = SessionConfig::for_account(account)
+            .resolve_with(&SecretServiceKeyring)
+            .unwrap();
+        assert_eq!(session.cookie, "second");
+
+        while let Some(item) = keyring_item(&ss, account).unwrap() {
+            item.delete().unwrap();
+        }
+        assert_eq!(SecretServiceKeyring.lookup(account).unwrap(), None);
+    }
+}
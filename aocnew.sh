@@ -4,7 +4,8 @@
 set -e
 
 # constants
-scaffold_path="${HOME}/.aoc_utils/scaffold"
+utils_path="${HOME}/.aoc_utils"
+scaffold_path="${utils_path}/scaffold"
 aoc_path="${HOME}/Documents/aoc2023"
 layout_dir="${HOME}/.i3/layouts/aoc"
 
@@ -28,6 +29,25 @@ fi
 
 project_name="aoc${date}lvl${level}"
 
+# make sure the input can be downloaded before creating anything, so this can just be run again once it can
+# (aocfetch caches it, and its exit codes are in the main README)
+fetch_status=0
+aocfetch > /dev/null || fetch_status=$?
+case ${fetch_status} in
+    0) ;;
+    3)
+        echo "aocfetch couldn't use the session cookie, log in again with: aocfetch session login" >&2
+        exit 3
+        ;;
+    5)
+        echo "day ${date} hasn't unlocked yet, run this again once it has" >&2
+        exit 5
+        ;;
+    *)
+        exit ${fetch_status}
+        ;;
+esac
+
 # aight we can do stuff now
 # set up a new cargo project
 mkdir -p "${level_dir}"
@@ -36,14 +56,39 @@ cargo new "${project_name}"
 cd "${project_name}"
 cargo add clap --features derive
 cargo add anyhow
+cargo add toml
+cargo add aocsub --path "${utils_path}/aocsub"
+cargo add aoc_parse --path "${utils_path}/aoc_parse"
+cargo add aoc_grid --path "${utils_path}/aoc_grid"
+cargo add aoc_search --path "${utils_path}/aoc_search"
+cargo add aoc_math --path "${utils_path}/aoc_math"
+# record which puzzle this is so the solution can submit its answer with --submit
+cat >> Cargo.toml <<EOF
+
+[package.metadata.aoc]
+year = ${year}
+day = $((10#${date}))
+level = ${level}
+EOF
 cp "${scaffold_path}/main.rs" "./src/main.rs"
 cp "${scaffold_path}/lib.rs" "./src/"
 git add .
 git commit -m "create project for day ${date} level ${level}"
 
-# download input
+# download input (aocfetch already cached it above)
 aocfetch -o input.txt
 
+# download the example and, if we can find its answer, generate a test that checks the solution against it
+if aocex -o example.txt; then
+    if answer=$(aocex --answer --level "${level}"); then
+        mkdir -p tests
+        # the answer goes in a rust string literal, so escape \ and " for rust first,
+        # then escape the characters that mean something in a sed replacement with | as the delimiter
+        answer=$(printf '%s' "${answer}" | sed -e 's/[\\"]/\\&/g' -e 's/[\\|&]/\\&/g')
+        sed -e "s|scaffold|${project_name}|" -e "s|EXPECTED_ANSWER|${answer}|" "${scaffold_path}/example.rs" > tests/example.rs
+    fi
+fi
+
 # open windows
 i3-msg "workspace 1; append_layout ${layout_dir}/workspace-1.json"
 code . &
@@ -0,0 +1,280 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+extern crate clap;
+use clap::Parser;
+
+extern crate thiserror;
+use thiserror::Error;
+
+extern crate toml;
+
+extern crate aocfetch;
+use aocfetch::answers::{AnswerError, AnswerStore};
+use aocfetch::cache;
+use aocfetch::session::parse_account;
+
+#[derive(Parser)]
+#[command(name = "aocrun")]
+#[command(author = "Daniel Gysi <danielgysi@protonmail.com>")]
+#[command(version = "0.1.0")]
+#[command(
+    about = "A command line utility to run every Advent of Code <https://adventofcode.com> solution in a folder and check the answers"
+)]
+struct Args {
+    /// the folder to search for solutions, e.g. a whole year (defaults to the current directory)
+    #[arg(default_value = ".")]
+    folder: PathBuf,
+
+    /// only run solutions for this year
+    #[arg(short, long)]
+    year: Option<u16>,
+    /// only run solutions for this day
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=31), short, long)]
+    day: Option<u8>,
+
+    /// record the answers from this run as the correct ones for puzzles without a recorded answer
+    #[arg(short, long)]
+    record: bool,
+    /// with --record, also replace answers that are already recorded
+    #[arg(long, requires = "record")]
+    force: bool,
+
+    /// the named account whose cached inputs and recorded answers to use
+    /// (defaults to the unnamed account)
+    #[arg(long, value_parser = parse_account)]
+    account: Option<String>,
+}
+
+/// configuration options for the app created based on cli args
+pub struct Config {
+    folder: PathBuf,
+    year: Option<u16>,
+    day: Option<u8>,
+    record: bool,
+    force: bool,
+    account: Option<String>,
+}
+
+/// construct app config from arguments
+impl Config {
+    pub fn make() -> Self {
+        let args = Args::parse();
+
+        Config {
+            folder: args.folder,
+            year: args.year,
+            day: args.day,
+            record: args.record,
+            force: args.force,
+            account: args.account,
+        }
+    }
+}
+
+/// an error encountered while running the application
+#[derive(Error, Debug)]
+pub enum RunError {
+    #[error("error occured while searching {0} for solutions: {1}")]
+    SearchError(PathBuf, io::Error),
+    #[error("no solutions with a [package.metadata.aoc] table in Cargo.toml found in {0}")]
+    NoSolutions(PathBuf),
+    #[error("error occured while loading or saving recorded answers: {0}")]
+    AnswerError(#[from] AnswerError),
+    #[error("{0} solution(s) gave a different answer from the recorded one or failed to give one")]
+    Regressions(usize),
+}
+
+/// a solution project found on disk, identified by the metadata aocnew.sh writes to Cargo.toml
+struct Solution {
+    folder: PathBuf,
+    name: String,
+    year: u16,
+    day: u8,
+    level: u8,
+}
+
+/// how running a solution went
+enum Outcome {
+    Answer(String, Duration),
+    Failed(String),
+}
+
+/// run the application according to the provided config
+pub fn run(cfg: Config) -> Result<(), RunError> {
+    let mut solutions = Vec::new();
+    find_solutions(&cfg.folder, 0, &mut solutions)?;
+    solutions
+        .retain(|s| cfg.year.is_none_or(|y| s.year == y) && cfg.day.is_none_or(|d| s.day == d));
+    if solutions.is_empty() {
+        return Err(RunError::NoSolutions(cfg.folder));
+    }
+    solutions.sort_by_key(|s| (s.year, s.day, s.level));
+
+    let account = cfg.account.as_deref();
+    let mut store = AnswerStore::open(account)?;
+    let mut regressions = 0;
+
+    if let Some(account) = account {
+        println!("account {account}");
+    }
+    println!(
+        "{:<4}  {:>3}  {:>5}  {:<20}  {:>10}  status",
+        "year", "day", "level", "answer", "time"
+    );
+    for solution in &solutions {
+        let expected = store
+            .get(solution.year, solution.day, solution.level)
+            .map(str::to_string);
+        let (answer, time, status) = match run_solution(solution, account) {
+            Outcome::Answer(answer, time) => {
+                // answers recorded by aocsub were accepted by the server, so only replace them when forced to
+                let record = cfg.record && (expected.is_none() || cfg.force);
+                let status = match expected.as_deref() {
+                    _ if record => "recorded".to_string(),
+                    Some(expected) if expected == answer => "ok".to_string(),
+                    Some(expected) => {
+                        regressions += 1;
+                        format!("REGRESSION (expected {expected})")
+                    }
+                    None => "unverified".to_string(),
+                };
+                if record {
+                    store.insert(solution.year, solution.day, solution.level, &answer);
+                }
+                (answer, format_duration(time), status)
+            }
+            // a solution that used to give the right answer and now gives none has regressed too
+            Outcome::Failed(reason) if expected.is_some() => {
+                regressions += 1;
+                (
+                    String::new(),
+                    String::new(),
+                    format!("REGRESSION ({reason})"),
+                )
+            }
+            Outcome::Failed(reason) => (String::new(), String::new(), format!("FAILED ({reason})")),
+        };
+        println!(
+            "{:<4}  {:>3}  {:>5}  {:<20}  {:>10}  {}",
+            solution.year, solution.day, solution.level, answer, time, status
+        );
+    }
+
+    if cfg.record {
+        store.save()?;
+    }
+
+    if regressions > 0 {
+        Err(RunError::Regressions(regressions))
+    } else {
+        Ok(())
+    }
+}
+
+/// look for solution projects in the folder and its subfolders (aocnew.sh puts them at DAY/LEVEL/NAME)
+fn find_solutions(folder: &Path, depth: usize, found: &mut Vec<Solution>) -> Result<(), RunError> {
+    const MAX_DEPTH: usize = 4;
+
+    if let Some(solution) = read_solution(folder) {
+        found.push(solution);
+        return Ok(());
+    }
+    if depth == MAX_DEPTH {
+        return Ok(());
+    }
+
+    let entries =
+        fs::read_dir(folder).map_err(|e| RunError::SearchError(folder.to_path_buf(), e))?;
+    for entry in entries {
+        let path = entry
+            .map_err(|e| RunError::SearchError(folder.to_path_buf(), e))?
+            .path();
+        let hidden_or_build = path
+            .file_name()
+            .is_some_and(|name| name == "target" || name.to_string_lossy().starts_with('.'));
+        if path.is_dir() && !hidden_or_build {
+            find_solutions(&path, depth + 1, found)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// read the puzzle metadata from a folder's Cargo.toml, if it's a solution project
+fn read_solution(folder: &Path) -> Option<Solution> {
+    let manifest: toml::Table = fs::read_to_string(folder.join("Cargo.toml"))
+        .ok()?
+        .parse()
+        .ok()?;
+    let package = manifest.get("package")?;
+    let puzzle = package.get("metadata")?.get("aoc")?;
+    let field = |name: &str| puzzle.get(name).and_then(|value| value.as_integer());
+
+    Some(Solution {
+        folder: folder.to_path_buf(),
+        name: package.get("name")?.as_str()?.to_string(),
+        year: field("year")?.try_into().ok()?,
+        day: field("day")?.try_into().ok()?,
+        level: field("level")?.try_into().ok()?,
+    })
+}
+
+/// build the solution, then run it on the account's input and time how long it takes
+fn run_solution(solution: &Solution, account: Option<&str>) -> Outcome {
+    // prefer the input aocfetch cached, falling back to the copy aocnew.sh saved in the project
+    let input_path = match cache::input_path(account, solution.year, solution.day) {
+        Some(path) if path.is_file() => path,
+        _ => solution.folder.join("input.txt"),
+    };
+    if !input_path.is_file() {
+        return Outcome::Failed("no input found".to_string());
+    }
+
+    let build = Command::new("cargo")
+        .args(["build", "--release", "--quiet", "--manifest-path"])
+        .arg(solution.folder.join("Cargo.toml"))
+        .output();
+    match build {
+        Ok(output) if output.status.success() => (),
+        Ok(_) => return Outcome::Failed("build failed".to_string()),
+        Err(e) => return Outcome::Failed(format!("couldn't run cargo: {e}")),
+    }
+
+    let mut binary = solution.folder.join("target/release");
+    binary.push(&solution.name);
+    let start = Instant::now();
+    let run = Command::new(&binary)
+        .arg("--input")
+        .arg(&input_path)
+        .output();
+    let time = start.elapsed();
+
+    match run {
+        Ok(output) if output.status.success() => {
+            // the scaffold prints the answer last, so anything before it is debugging output
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            match stdout.lines().rev().find(|line| !line.trim().is_empty()) {
+                Some(answer) => Outcome::Answer(answer.trim().to_string(), time),
+                None => Outcome::Failed("no answer printed".to_string()),
+            }
+        }
+        Ok(output) => Outcome::Failed(format!("exited with {}", output.status)),
+        Err(e) => Outcome::Failed(format!("couldn't run {}: {e}", binary.display())),
+    }
+}
+
+/// format a duration with a sensible unit for the table
+fn format_duration(time: Duration) -> String {
+    let micros = time.as_micros();
+    if micros < 1000 {
+        format!("{micros}µs")
+    } else if micros < 1_000_000 {
+        format!("{:.1}ms", micros as f64 / 1000.0)
+    } else {
+        format!("{:.2}s", time.as_secs_f64())
+    }
+}
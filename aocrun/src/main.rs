@@ -0,0 +1,9 @@
+use aocrun::Config;
+use std::process;
+fn main() {
+    if let Err(e) = aocrun::run(Config::make()) {
+        eprintln!("ERROR {}", e);
+        process::exit(1);
+    }
+    process::exit(0);
+}
@@ -0,0 +1,98 @@
+extern crate regex;
+use regex::Regex;
+
+extern crate serde;
+use serde::Serialize;
+
+/// the stars earned on each unlocked day of a year, as shown on the year's calendar page
+#[derive(Serialize)]
+pub struct Calendar {
+    pub year: u16,
+    pub days: Vec<DayStars>,
+    /// the total number of stars earned in the year
+    pub stars: u32,
+}
+
+/// the stars earned on a single day
+#[derive(Serialize)]
+pub struct DayStars {
+    pub day: u8,
+    pub stars: u8,
+}
+
+impl Calendar {
+    /// the most stars that could have been earned on the unlocked days
+    pub fn possible_stars(&self) -> u32 {
+        2 * self.days.len() as u32
+    }
+
+    /// the stars earned on the day, or None if it hasn't been unlocked
+    pub fn stars_on(&self, day: u8) -> Option<u8> {
+        self.days.iter().find(|d| d.day == day).map(|d| d.stars)
+    }
+
+    /// the last day that's been unlocked
+    pub fn last_day(&self) -> u8 {
+        self.days.iter().map(|d| d.day).max().unwrap_or(0)
+    }
+}
+
+/// given the raw html of a year's page, read the stars for each unlocked day from the calendar's classes
+/// unlocked days are links with a class like `calendar-day3`, followed by `calendar-complete` for one star
+/// or `calendar-verycomplete` for two
+pub fn parse_calendar(year: u16, page: &str) -> Calendar {
+    const PATTERN: &str = r#"<a [^>]*class="calendar-day(\d+)([^"]*)""#;
+    let reg = Regex::new(PATTERN).expect("couldn't make regex");
+
+    let mut days: Vec<DayStars> = reg
+        .captures_iter(page)
+        .filter_map(|c| {
+            let day = c[1].parse().ok()?;
+            let classes: Vec<&str> = c[2].split_whitespace().collect();
+            let stars = if classes.contains(&"calendar-verycomplete") {
+                2
+            } else if classes.contains(&"calendar-complete") {
+                1
+            } else {
+                0
+            };
+            Some(DayStars { day, stars })
+        })
+        .collect();
+    days.sort_by_key(|d| d.day);
+    days.dedup_by_key(|d| d.day);
+
+    let stars = days.iter().map(|d| d.stars as u32).sum();
+    Calendar { year, days, stars }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a trimmed copy of /2019 with two stars on days 1 and 4, one on day 2 and none on day 3
+    const CALENDAR: &str = include_str!("../fixtures/calendar.html");
+
+    #[test]
+    fn stars_per_day() {
+        let calendar = parse_calendar(2019, CALENDAR);
+        assert_eq!(calendar.year, 2019);
+        let days: Vec<(u8, u8)> = calendar.days.iter().map(|d| (d.day, d.stars)).collect();
+        assert_eq!(days, vec![(1, 2), (2, 1), (3, 0), (4, 2)]);
+        assert_eq!(calendar.stars, 5);
+        assert_eq!(calendar.possible_stars(), 8);
+        assert_eq!(calendar.last_day(), 4);
+        assert_eq!(calendar.stars_on(2), Some(1));
+        assert_eq!(calendar.stars_on(3), Some(0));
+        // locked days aren't links, so they aren't unlocked days with no stars
+        assert_eq!(calendar.stars_on(5), None);
+    }
+
+    #[test]
+    fn nothing_unlocked() {
+        let calendar = parse_calendar(2030, "<main><p>Nothing to see here.</p></main>");
+        assert!(calendar.days.is_empty());
+        assert_eq!(calendar.stars, 0);
+        assert_eq!(calendar.last_day(), 0);
+    }
+}
@@ -0,0 +1,261 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+extern crate thiserror;
+use thiserror::Error;
+
+extern crate serde;
+use serde::{Deserialize, Serialize};
+
+extern crate serde_json;
+
+extern crate dirs;
+
+/// adventofcode.com asks that the leaderboard api isn't requested more than once every 15 minutes
+pub const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60);
+
+/// error encountered while loading or caching a private leaderboard
+#[derive(Error, Debug)]
+pub enum LeaderboardError {
+    #[error("unable to find a cache directory for this platform")]
+    NoCacheDir,
+    #[error("unable to create cache directory {0}: {1}")]
+    DirCreationError(PathBuf, io::Error),
+    #[error("unable to write cached leaderboard to {0}: {1}")]
+    WriteError(PathBuf, io::Error),
+    #[error("unable to parse leaderboard (check the id and that you can view it): {0}")]
+    ParseError(serde_json::Error),
+}
+
+/// a private leaderboard as returned by /YEAR/leaderboard/private/view/ID.json
+#[derive(Deserialize, Clone)]
+pub struct Leaderboard {
+    pub members: HashMap<String, Member>,
+}
+
+/// a member of a private leaderboard
+#[derive(Deserialize, Clone)]
+pub struct Member {
+    pub id: u64,
+    pub name: Option<String>,
+    pub stars: u32,
+    pub local_score: u32,
+    pub last_star_ts: i64,
+    /// when each star was earned, by day then level
+    pub completion_day_level: BTreeMap<u8, BTreeMap<u8, Star>>,
+}
+
+/// a single star earned by a member
+#[derive(Deserialize, Clone)]
+pub struct Star {
+    pub get_star_ts: i64,
+}
+
+impl Member {
+    /// the member's name, or what adventofcode.com shows for members who haven't set one
+    pub fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    /// when the member earned the star for the day and level, as a unix timestamp
+    pub fn star_ts(&self, day: u8, level: u8) -> Option<i64> {
+        let star = self.completion_day_level.get(&day)?.get(&level)?;
+        Some(star.get_star_ts)
+    }
+
+    /// the number of stars the member earned on the day
+    pub fn stars_on(&self, day: u8) -> usize {
+        self.completion_day_level.get(&day).map_or(0, BTreeMap::len)
+    }
+}
+
+impl Leaderboard {
+    pub fn parse(json: &str) -> Result<Self, LeaderboardError> {
+        serde_json::from_str(json).map_err(LeaderboardError::ParseError)
+    }
+
+    /// the members in the order adventofcode.com ranks them: by local score, then by who got there first
+    pub fn ranked(&self) -> Vec<&Member> {
+        let mut members: Vec<&Member> = self.members.values().collect();
+        members.sort_by_key(|m| (u32::MAX - m.local_score, m.last_star_ts, m.id));
+        members
+    }
+
+    /// each member's rank (starting at 1) by id
+    pub fn ranks(&self) -> HashMap<u64, usize> {
+        self.ranked()
+            .iter()
+            .enumerate()
+            .map(|(i, m)| (m.id, i + 1))
+            .collect()
+    }
+
+    /// the member with the id
+    pub fn member(&self, id: u64) -> Option<&Member> {
+        self.members.values().find(|m| m.id == id)
+    }
+
+    /// the last day anyone has earned a star on
+    pub fn last_day(&self) -> u8 {
+        self.members
+            .values()
+            .filter_map(|m| m.completion_day_level.keys().max())
+            .max()
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+/// something that changed on a leaderboard between two fetches
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    NewMember {
+        id: u64,
+        name: String,
+    },
+    NewStar {
+        id: u64,
+        name: String,
+        day: u8,
+        level: u8,
+        ts: i64,
+    },
+    RankChange {
+        id: u64,
+        name: String,
+        old_rank: usize,
+        new_rank: usize,
+    },
+}
+
+impl Event {
+    /// the day a new star was earned on, or None for events that aren't about a single day
+    pub fn day(&self) -> Option<u8> {
+        match self {
+            Event::NewStar { day, .. } => Some(*day),
+            Event::NewMember { .. } | Event::RankChange { .. } => None,
+        }
+    }
+}
+
+/// what changed between two fetches of a leaderboard: new members, then new stars in the order they were earned,
+/// then rank changes from the top of the leaderboard down
+pub fn events(before: &Leaderboard, after: &Leaderboard) -> Vec<Event> {
+    let mut new_members = Vec::new();
+    let mut new_stars = Vec::new();
+    for member in after.members.values() {
+        let old = before.member(member.id);
+        if old.is_none() {
+            new_members.push(Event::NewMember {
+                id: member.id,
+                name: member.display_name(),
+            });
+        }
+        for (&day, levels) in &member.completion_day_level {
+            for (&level, star) in levels {
+                if old.and_then(|o| o.star_ts(day, level)).is_none() {
+                    new_stars.push(Event::NewStar {
+                        id: member.id,
+                        name: member.display_name(),
+                        day,
+                        level,
+                        ts: star.get_star_ts,
+                    });
+                }
+            }
+        }
+    }
+    new_members.sort_by_key(|e| match e {
+        Event::NewMember { id, .. } => *id,
+        _ => 0,
+    });
+    new_stars.sort_by_key(|e| match e {
+        Event::NewStar { ts, id, .. } => (*ts, *id),
+        _ => (0, 0),
+    });
+
+    // members who just joined don't have an old rank to change from
+    let old_ranks = before.ranks();
+    let rank_changes = after
+        .ranked()
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, member)| {
+            let old_rank = *old_ranks.get(&member.id)?;
+            (old_rank != i + 1).then(|| Event::RankChange {
+                id: member.id,
+                name: member.display_name(),
+                old_rank,
+                new_rank: i + 1,
+            })
+        });
+
+    new_members
+        .into_iter()
+        .chain(new_stars)
+        .chain(rank_changes)
+        .collect()
+}
+
+/// where a leaderboard is cached (~/.cache/aoc_utils/leaderboards/YEAR/ID.json on linux)
+/// the fetch before that one is kept alongside it as ID.previous.json so changes can be shown
+/// each named account has its own copies in ~/.cache/aoc_utils/accounts/ACCOUNT/leaderboards/
+fn cache_path(account: Option<&str>, year: u16, id: u64, previous: bool) -> Option<PathBuf> {
+    let mut path = dirs::cache_dir()?;
+    path.push("aoc_utils");
+    if let Some(account) = account {
+        path.push("accounts");
+        path.push(account);
+    }
+    path.push("leaderboards");
+    path.push(year.to_string());
+    if previous {
+        path.push(format!("{id}.previous.json"));
+    } else {
+        path.push(format!("{id}.json"));
+    }
+    Some(path)
+}
+
+/// the latest cached copy of a leaderboard and how long ago it was fetched
+pub fn load_cached(account: Option<&str>, year: u16, id: u64) -> Option<(String, Duration)> {
+    let path = cache_path(account, year, id, false)?;
+    let age = fs::metadata(&path)
+        .ok()?
+        .modified()
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .unwrap_or_default();
+    Some((fs::read_to_string(path).ok()?, age))
+}
+
+/// the copy of a leaderboard fetched before the latest one
+pub fn load_previous(account: Option<&str>, year: u16, id: u64) -> Option<String> {
+    fs::read_to_string(cache_path(account, year, id, true)?).ok()
+}
+
+/// cache a freshly fetched leaderboard, keeping the copy it replaces as the previous one
+pub fn store(
+    account: Option<&str>,
+    year: u16,
+    id: u64,
+    json: &str,
+) -> Result<(), LeaderboardError> {
+    let path = cache_path(account, year, id, false).ok_or(LeaderboardError::NoCacheDir)?;
+    let previous = cache_path(account, year, id, true).ok_or(LeaderboardError::NoCacheDir)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| LeaderboardError::DirCreationError(dir.to_path_buf(), e))?;
+    }
+    if path.is_file() {
+        fs::rename(&path, &previous).map_err(|e| LeaderboardError::WriteError(previous, e))?;
+    }
+    fs::write(&path, json).map_err(|e| LeaderboardError::WriteError(path, e))
+}
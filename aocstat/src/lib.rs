@@ -0,0 +1,631 @@
+use std::io::{self, Write};
+use std::process::{self, Stdio};
+use std::thread;
+use std::time::Duration;
+
+extern crate clap;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, Subcommand};
+
+extern crate chrono;
+use chrono::{DateTime, Datelike, FixedOffset};
+
+extern crate thiserror;
+use thiserror::Error;
+
+extern crate serde_json;
+
+extern crate aocfetch;
+use aocfetch::fetches::{FetchLog, FetchLogError};
+use aocfetch::get_aoc_time;
+use aocfetch::request::{self, RequestError};
+use aocfetch::session::{SessionArgs, SessionConfig, SessionError};
+
+mod calendar;
+use calendar::Calendar;
+
+mod stats;
+
+mod personal;
+use personal::PersonalError;
+
+mod leaderboard;
+use leaderboard::{Event, Leaderboard, LeaderboardError, REFRESH_INTERVAL};
+
+#[derive(Parser)]
+#[command(name = "aocstat")]
+#[command(author = "Daniel Gysi <danielgysi@protonmail.com>")]
+#[command(version = "0.1.0")]
+#[command(
+    about = "A command line utility to show your progress in Advent of Code <https://adventofcode.com>"
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Command,
+
+    #[command(flatten)]
+    session: SessionArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// show the stars you've earned on each day as a grid
+    Status {
+        /// the year to show, can be given more than once
+        /// (defaults to current year if UTC-5 is December, otherwise the previous year)
+        #[arg(value_parser = clap::value_parser!(u16).range(2015..), short, long)]
+        year: Vec<u16>,
+        /// show every year since 2015
+        #[arg(short, long, conflicts_with = "year")]
+        all: bool,
+        /// print the stars as json instead of a grid
+        #[arg(short, long)]
+        json: bool,
+    },
+    /// show your personal times, ranks and scores for each day from /YEAR/leaderboard/self,
+    /// and how they've changed across every year you've looked at
+    #[command(name = "self")]
+    Personal {
+        /// the year to show, can be given more than once
+        /// (defaults to current year if UTC-5 is December, otherwise the previous year)
+        #[arg(value_parser = clap::value_parser!(u16).range(2015..), short, long)]
+        year: Vec<u16>,
+        /// show every year since 2015
+        #[arg(short, long, conflicts_with = "year")]
+        all: bool,
+        /// only show the stats stored from previous runs instead of fetching them
+        #[arg(short, long)]
+        offline: bool,
+    },
+    /// show a private leaderboard ranked by local score, with the changes since it was last fetched
+    Leaderboard {
+        #[command(subcommand)]
+        command: Option<LeaderboardCommand>,
+
+        /// the id of the private leaderboard (the number at the end of its url)
+        id: u64,
+        /// the year to show
+        /// (defaults to current year if UTC-5 is December, otherwise the previous year)
+        #[arg(value_parser = clap::value_parser!(u16).range(2015..), short, long, global = true)]
+        year: Option<u16>,
+        /// show when each member earned their stars on this day instead of a grid of every day
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=25), short, long, global = true)]
+        day: Option<u8>,
+    },
+}
+
+#[derive(Subcommand)]
+enum LeaderboardCommand {
+    /// check the leaderboard every 15 minutes and report new members, new stars and rank changes
+    /// as lines of json, or only the new stars on a single day with --day
+    Watch {
+        /// a command to run with sh for each event, with the event as a line of json on its stdin
+        /// (defaults to printing the events to stdout)
+        #[arg(short, long)]
+        exec: Option<String>,
+    },
+    /// show each member's fair score, which ranks each day by the time between part 1 and part 2,
+    /// their fetch score, which ranks each day by the time since aocfetch first fetched the input
+    /// (only for members who used it on this computer), and their median solve times,
+    /// or how everyone did on a single day with --day
+    Stats {
+        /// print the stats as csv, with times in seconds, instead of a table
+        #[arg(long)]
+        csv: bool,
+    },
+}
+
+/// configuration options for the app created based on cli args
+pub struct Config {
+    action: Action,
+    session_cfg: SessionConfig,
+    /// the named account, or None for the unnamed one
+    account: Option<String>,
+}
+
+/// keep track of what the application will show
+enum Action {
+    Status {
+        years: Vec<u16>,
+        json: bool,
+    },
+    Personal {
+        years: Vec<u16>,
+        offline: bool,
+    },
+    Leaderboard {
+        year: u16,
+        id: u64,
+        day: Option<u8>,
+    },
+    Watch {
+        year: u16,
+        id: u64,
+        day: Option<u8>,
+        exec: Option<String>,
+    },
+    Stats {
+        year: u16,
+        id: u64,
+        day: Option<u8>,
+        csv: bool,
+    },
+}
+
+/// construct app config from arguments
+impl Config {
+    pub fn make() -> Self {
+        let args = Args::parse();
+
+        // how will we get the session cookie?
+        let account = args.session.account();
+        let session_cfg = SessionConfig::from(args.session);
+
+        // time sensitive config
+        let dt = get_aoc_time();
+        let latest_year = if dt.month() == 12 {
+            dt.year() as u16
+        } else {
+            dt.year() as u16 - 1
+        };
+
+        let action = match args.command {
+            Command::Status { year, all, json } => {
+                // figure out the years
+                let years = choose_years(year, all, latest_year);
+                Action::Status { years, json }
+            }
+            Command::Personal { year, all, offline } => {
+                let years = choose_years(year, all, latest_year);
+                Action::Personal { years, offline }
+            }
+            Command::Leaderboard {
+                command,
+                id,
+                year,
+                day,
+            } => {
+                let year = year.map_or(latest_year, |y| check_year(y, latest_year));
+                match command {
+                    Some(LeaderboardCommand::Watch { exec }) => Action::Watch {
+                        year,
+                        id,
+                        day,
+                        exec,
+                    },
+                    Some(LeaderboardCommand::Stats { csv }) => Action::Stats { year, id, day, csv },
+                    None => Action::Leaderboard { year, id, day },
+                }
+            }
+        };
+
+        Config {
+            action,
+            session_cfg,
+            account,
+        }
+    }
+}
+
+/// the years given, or every year, or the latest one if none were given
+fn choose_years(years: Vec<u16>, all: bool, latest_year: u16) -> Vec<u16> {
+    if all {
+        (2015..=latest_year).collect()
+    } else if years.is_empty() {
+        vec![latest_year]
+    } else {
+        years
+            .into_iter()
+            .map(|y| check_year(y, latest_year))
+            .collect()
+    }
+}
+
+/// custom clap validation for a user-provided year that hasn't started yet
+fn check_year(year: u16, latest_year: u16) -> u16 {
+    if year <= latest_year {
+        year
+    } else {
+        let mut cmd = Args::command();
+        cmd.error(
+            ErrorKind::InvalidValue,
+            "a year provided hasn't started yet for UTC-5",
+        )
+        .exit();
+    }
+}
+
+/// an error encountered while running the application
+#[derive(Error, Debug)]
+pub enum RunError {
+    #[error("error retrieving session cookie: {0}")]
+    SessionError(#[from] SessionError),
+    #[error("error occurred while making a request to adventofcode.com: {0}")]
+    RequestError(#[from] RequestError),
+    #[error("error occured while storing or loading personal stats: {0}")]
+    PersonalError(#[from] PersonalError),
+    #[error("error occured while loading the leaderboard: {0}")]
+    LeaderboardError(#[from] LeaderboardError),
+    #[error("error occured while loading when inputs were first fetched: {0}")]
+    FetchLogError(#[from] FetchLogError),
+    #[error("error occured while attempting to write to stdout: {0}")]
+    StdoutError(io::Error),
+}
+
+impl RunError {
+    /// whether an error while watching should stop the watch instead of being reported and tried again
+    /// at the next poll, which is only when the events can't be written out any more
+    fn ends_watch(&self) -> bool {
+        matches!(self, RunError::StdoutError(_))
+    }
+}
+
+/// run the application according to the provided config
+pub fn run(cfg: Config) -> Result<(), RunError> {
+    let mut out = match cfg.action {
+        Action::Status { years, json } => {
+            let session_cookie = cfg.session_cfg.resolve()?;
+            let calendars: Vec<Calendar> = fetch_years(&years, "", &session_cookie)?
+                .into_iter()
+                .map(|(year, page)| calendar::parse_calendar(year, &page))
+                .collect();
+            if json {
+                serde_json::to_string_pretty(&calendars).expect("couldn't serialize calendars")
+            } else {
+                format_status(&calendars)
+            }
+        }
+        Action::Personal { years, offline } => {
+            // each account keeps its own stats
+            let account = cfg.account.as_deref();
+            if !offline {
+                let session_cookie = cfg.session_cfg.resolve()?;
+                for (year, page) in fetch_years(&years, "/leaderboard/self", &session_cookie)? {
+                    personal::store(account, &personal::parse_page(year, &page))?;
+                }
+            }
+            let stats = format_personal(&personal::load_all(account)?, &years);
+            match account {
+                Some(account) => format!("account {account}\n{stats}"),
+                None => stats,
+            }
+        }
+        Action::Leaderboard { year, id, day } => {
+            let account = cfg.account.as_deref();
+            let (board, age) = load_leaderboard(account, year, id, &cfg.session_cfg)?;
+            // a previous copy that can't be parsed just means there's nothing to compare against
+            let previous = leaderboard::load_previous(account, year, id)
+                .and_then(|json| Leaderboard::parse(&json).ok());
+            format_leaderboard(&board, previous.as_ref(), age, day)
+        }
+        Action::Watch {
+            year,
+            id,
+            day,
+            exec,
+        } => {
+            return watch(
+                cfg.account.as_deref(),
+                year,
+                id,
+                day,
+                &cfg.session_cfg,
+                exec,
+            )
+        }
+        Action::Stats { year, id, day, csv } => {
+            let (board, _) = load_leaderboard(cfg.account.as_deref(), year, id, &cfg.session_cfg)?;
+            // every local account's first fetches count, not just the one whose cookie fetched the leaderboard
+            let fetches = stats::first_fetches(&board, year, &FetchLog::open_all()?);
+            match day {
+                Some(day) => {
+                    stats::format_day_results(&stats::day_results(&board, year, day, &fetches), csv)
+                }
+                None => {
+                    stats::format_member_stats(&stats::member_stats(&board, year, &fetches), csv)
+                }
+            }
+        }
+    };
+    out.push('\n');
+
+    io::stdout()
+        .write_all(out.as_bytes())
+        .map_err(RunError::StdoutError)?;
+
+    Ok(())
+}
+
+/// fetch a page for each year, e.g. /YEAR when the path is empty or /YEAR/leaderboard/self
+fn fetch_years(
+    years: &[u16],
+    path: &str,
+    session_cookie: &str,
+) -> Result<Vec<(u16, String)>, RequestError> {
+    // be polite to the server when fetching several years
+    const REQUEST_DELAY: Duration = Duration::from_millis(500);
+
+    let mut pages = Vec::new();
+    for (i, &year) in years.iter().enumerate() {
+        if i > 0 {
+            thread::sleep(REQUEST_DELAY);
+        }
+        let url = format!("https://adventofcode.com/{year}{path}");
+        pages.push((year, request::get_page(&url, session_cookie)?));
+    }
+    Ok(pages)
+}
+
+/// the stats for each of the years, followed by the trends across every stored year if there's more than one
+fn format_personal(all: &[personal::YearStats], years: &[u16]) -> String {
+    let mut sections: Vec<String> = years
+        .iter()
+        .map(|&year| match all.iter().find(|s| s.year == year) {
+            Some(stats) if !stats.days.is_empty() => personal::format_year(stats),
+            Some(_) => format!("{year}\nno stars yet"),
+            None => format!("{year}\nno stats stored, run without --offline to fetch them"),
+        })
+        .collect();
+    if all.len() > 1 {
+        sections.push(format!("trends\n{}", personal::format_trends(all)));
+    }
+    sections.join("\n\n")
+}
+
+/// a row per year with a column per day, followed by a summary if there's more than one year
+/// `**` is both stars, `* ` is one, ` .` is none and a blank is a day that hasn't been unlocked
+fn format_status(calendars: &[Calendar]) -> String {
+    // later years have fewer days, so only show as many columns as the longest one needs
+    let days = calendars.iter().map(Calendar::last_day).max().unwrap_or(0);
+
+    let mut lines = Vec::new();
+    let header: String = (1..=days).map(|day| format!(" {day:>2}")).collect();
+    lines.push(format!("year{header}  stars"));
+    for calendar in calendars {
+        let row: String = (1..=days)
+            .map(|day| match calendar.stars_on(day) {
+                Some(2) => " **",
+                Some(1) => " * ",
+                Some(_) => "  .",
+                None => "   ",
+            })
+            .collect();
+        lines.push(format!(
+            "{}{row}  {}/{}",
+            calendar.year,
+            calendar.stars,
+            calendar.possible_stars()
+        ));
+    }
+
+    if calendars.len() > 1 {
+        let stars: u32 = calendars.iter().map(|c| c.stars).sum();
+        let possible: u32 = calendars.iter().map(Calendar::possible_stars).sum();
+        let complete = calendars
+            .iter()
+            .filter(|c| c.stars > 0 && c.stars == c.possible_stars())
+            .count();
+        lines.push(format!(
+            "{stars}/{possible} stars across {} years, {complete} of them complete",
+            calendars.len()
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// the leaderboard from the cache if it was fetched recently enough, otherwise from adventofcode.com
+/// along with how long ago it was fetched
+fn load_leaderboard(
+    account: Option<&str>,
+    year: u16,
+    id: u64,
+    session_cfg: &SessionConfig,
+) -> Result<(Leaderboard, Duration), RunError> {
+    if let Some((json, age)) = leaderboard::load_cached(account, year, id) {
+        if age < REFRESH_INTERVAL {
+            return Ok((Leaderboard::parse(&json)?, age));
+        }
+    }
+
+    let session_cookie = session_cfg.resolve()?;
+    let url = format!("https://adventofcode.com/{year}/leaderboard/private/view/{id}.json");
+    let json = request::get_page(&url, &session_cookie)?;
+    // make sure it's really a leaderboard before replacing the cached copy
+    let board = Leaderboard::parse(&json)?;
+    leaderboard::store(account, year, id, &json)?;
+    Ok((board, Duration::ZERO))
+}
+
+/// poll the leaderboard as often as adventofcode.com allows and report what changed each time, forever
+/// with a day, only the new stars on that day are reported
+/// errors are reported on stderr and tried again at the next poll, except at startup or when stdout is gone
+fn watch(
+    account: Option<&str>,
+    year: u16,
+    id: u64,
+    day: Option<u8>,
+    session_cfg: &SessionConfig,
+    exec: Option<String>,
+) -> Result<(), RunError> {
+    let (mut board, mut age) = load_leaderboard(account, year, id, session_cfg)?;
+    loop {
+        thread::sleep(REFRESH_INTERVAL.saturating_sub(age));
+        let result =
+            load_leaderboard(account, year, id, session_cfg).and_then(|(next, next_age)| {
+                for event in leaderboard::events(&board, &next) {
+                    if day.is_none_or(|day| event.day() == Some(day)) {
+                        emit(&event, exec.as_deref())?;
+                    }
+                }
+                Ok((next, next_age))
+            });
+        match result {
+            Ok((next, next_age)) => {
+                board = next;
+                age = next_age;
+            }
+            Err(e) if e.ends_watch() => return Err(e),
+            Err(e) => {
+                eprintln!("ERROR {e}");
+                age = Duration::ZERO;
+            }
+        }
+    }
+}
+
+/// report an event as a line of json on stdout, or on the stdin of the command if there is one
+/// a command that fails is reported on stderr rather than stopping the watch
+fn emit(event: &Event, exec: Option<&str>) -> Result<(), RunError> {
+    let mut line = serde_json::to_string(event).expect("couldn't serialize event");
+    line.push('\n');
+
+    let Some(exec) = exec else {
+        return io::stdout()
+            .write_all(line.as_bytes())
+            .map_err(RunError::StdoutError);
+    };
+
+    let result = process::Command::new("sh")
+        .arg("-c")
+        .arg(exec)
+        .stdin(Stdio::piped())
+        .spawn()
+        .and_then(|mut child| {
+            if let Some(mut stdin) = child.stdin.take() {
+                stdin.write_all(line.as_bytes())?;
+            }
+            child.wait()
+        });
+    match result {
+        Ok(status) if !status.success() => eprintln!("ERROR {exec} exited with {status}"),
+        Err(e) => eprintln!("ERROR unable to run {exec}: {e}"),
+        Ok(_) => (),
+    }
+    Ok(())
+}
+
+/// a row per member in rank order with their score and stars and how those changed since the previous fetch,
+/// followed by a grid of stars for every day or when they earned each star on a single day
+fn format_leaderboard(
+    board: &Leaderboard,
+    previous: Option<&Leaderboard>,
+    age: Duration,
+    day: Option<u8>,
+) -> String {
+    let mut lines = Vec::new();
+    let minutes = age.as_secs() / 60;
+    let next = (REFRESH_INTERVAL.as_secs() / 60).saturating_sub(minutes);
+    if minutes == 0 {
+        lines.push("fetched just now".to_string());
+    } else {
+        lines.push(format!(
+            "fetched {minutes} minute(s) ago, it can be refreshed in {next} minute(s)"
+        ));
+    }
+
+    let days = board.last_day();
+    let header = match day {
+        Some(_) => format!("  {:<14}  {:<14}", "part 1", "part 2"),
+        None => (1..=days).map(|day| format!(" {day:>2}")).collect(),
+    };
+    lines.push(format!(
+        "{:>4} {:>4}  {:>5} {:>4}  {:>5} {:>3} {header}  name",
+        "rank", "", "score", "", "stars", ""
+    ));
+
+    let previous_ranks = previous.map(Leaderboard::ranks);
+    for (i, member) in board.ranked().into_iter().enumerate() {
+        let rank = i + 1;
+        let before = previous.and_then(|p| p.member(member.id));
+        let rank_change = match previous_ranks.as_ref().map(|r| r.get(&member.id)) {
+            Some(Some(&old)) if old > rank => format!("+{}", old - rank),
+            Some(Some(&old)) if old < rank => format!("-{}", rank - old),
+            Some(None) => "new".to_string(),
+            _ => String::new(),
+        };
+        let score_change = increase(before.map(|b| b.local_score), member.local_score);
+        let star_change = increase(before.map(|b| b.stars), member.stars);
+
+        let cells = match day {
+            Some(day) => format!(
+                "  {:<14}  {:<14}",
+                member
+                    .star_ts(day, 1)
+                    .map(format_timestamp)
+                    .unwrap_or_default(),
+                member
+                    .star_ts(day, 2)
+                    .map(format_timestamp)
+                    .unwrap_or_default()
+            ),
+            None => (1..=days)
+                .map(|day| match member.stars_on(day) {
+                    2 => " **",
+                    1 => " * ",
+                    _ => "  .",
+                })
+                .collect(),
+        };
+
+        lines.push(format!(
+            "{rank:>4} {rank_change:>4}  {:>5} {score_change:>4}  {:>5} {star_change:>3} {cells}  {}",
+            member.local_score,
+            member.stars,
+            member.display_name()
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// how much a number went up since the previous fetch, or nothing if it didn't
+fn increase(before: Option<u32>, now: u32) -> String {
+    match before {
+        Some(before) if now > before => format!("+{}", now - before),
+        _ => String::new(),
+    }
+}
+
+/// a unix timestamp as a date and time in UTC-5, where midnight is when each puzzle unlocks
+fn format_timestamp(ts: i64) -> String {
+    // seconds in an hour
+    const HOUR: i32 = 3600;
+
+    let offset = FixedOffset::west_opt(5 * HOUR).unwrap();
+    DateTime::from_timestamp(ts, 0)
+        .map(|dt| {
+            dt.with_timezone(&offset)
+                .format("%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watch_carries_on_after_poll_errors() {
+        // e.g. a maintenance page instead of the leaderboard json
+        let parse_error = serde_json::from_str::<serde_json::Value>("<html>").unwrap_err();
+        let carries_on = [
+            RunError::RequestError(RequestError::BadResponse(500)),
+            RunError::RequestError(RequestError::LoggedOut),
+            RunError::LeaderboardError(LeaderboardError::ParseError(parse_error)),
+            RunError::LeaderboardError(LeaderboardError::WriteError(
+                "board.json".into(),
+                io::Error::other("disk full"),
+            )),
+            RunError::SessionError(SessionError::MissingCookie),
+            RunError::PersonalError(PersonalError::NoDataDir),
+        ];
+        for e in carries_on {
+            assert!(!e.ends_watch(), "{e}");
+        }
+
+        let stdout_gone = RunError::StdoutError(io::ErrorKind::BrokenPipe.into());
+        assert!(stdout_gone.ends_watch());
+    }
+}
@@ -0,0 +1,9 @@
+use aocstat::Config;
+use std::process;
+fn main() {
+    if let Err(e) = aocstat::run(Config::make()) {
+        eprintln!("ERROR {}", e);
+        process::exit(1);
+    }
+    process::exit(0);
+}
@@ -0,0 +1,335 @@
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+extern crate thiserror;
+use thiserror::Error;
+
+extern crate regex;
+use regex::Regex;
+
+extern crate serde;
+use serde::{Deserialize, Serialize};
+
+extern crate serde_json;
+
+extern crate dirs;
+
+use crate::stats::{format_seconds, median, table};
+
+/// error encountered while storing or loading personal stats
+#[derive(Error, Debug)]
+pub enum PersonalError {
+    #[error("unable to find a data directory for this platform")]
+    NoDataDir,
+    #[error("unable to read stored stats from {0}: {1}")]
+    ReadError(PathBuf, io::Error),
+    #[error("unable to parse stored stats in {0}: {1}")]
+    ParseError(PathBuf, serde_json::Error),
+    #[error("unable to create data directory {0}: {1}")]
+    DirCreationError(PathBuf, io::Error),
+    #[error("unable to write stats to {0}: {1}")]
+    WriteError(PathBuf, io::Error),
+}
+
+/// the personal stats for a year, as shown on /YEAR/leaderboard/self
+#[derive(Serialize, Deserialize)]
+pub struct YearStats {
+    pub year: u16,
+    /// the days with at least one star, in order
+    pub days: Vec<DayStats>,
+}
+
+/// the personal stats for each part of a day
+#[derive(Serialize, Deserialize)]
+pub struct DayStats {
+    pub day: u8,
+    pub part1: Option<PartStats>,
+    pub part2: Option<PartStats>,
+}
+
+/// the personal stats for a solved part
+#[derive(Serialize, Deserialize)]
+pub struct PartStats {
+    /// seconds from when the puzzle unlocked until it was solved, or None if it took more than 24 hours
+    pub time: Option<u32>,
+    /// rank and score on the global leaderboard, which aren't shown for every year or every solve
+    pub rank: Option<u32>,
+    pub score: Option<u32>,
+}
+
+impl YearStats {
+    pub fn stars(&self) -> usize {
+        self.days
+            .iter()
+            .map(|d| d.part1.is_some() as usize + d.part2.is_some() as usize)
+            .sum()
+    }
+
+    /// the times for part 1 or 2 that were under 24 hours
+    pub fn times(&self, level: u8) -> Vec<i64> {
+        self.days
+            .iter()
+            .filter_map(|d| if level == 1 { &d.part1 } else { &d.part2 }.as_ref())
+            .filter_map(|p| p.time.map(i64::from))
+            .collect()
+    }
+
+    /// the best global rank for either part
+    pub fn best_rank(&self) -> Option<u32> {
+        self.parts().filter_map(|p| p.rank).min()
+    }
+
+    /// the total global leaderboard score
+    pub fn score(&self) -> u32 {
+        self.parts().filter_map(|p| p.score).sum()
+    }
+
+    fn parts(&self) -> impl Iterator<Item = &PartStats> {
+        self.days
+            .iter()
+            .flat_map(|d| d.part1.iter().chain(d.part2.iter()))
+    }
+}
+
+/// parse a time like 01:23:45 into seconds, or None for >24h
+fn parse_time(time: &str) -> Option<u32> {
+    let mut seconds = 0;
+    for field in time.split(':') {
+        seconds = seconds * 60 + field.parse::<u32>().ok()?;
+    }
+    Some(seconds)
+}
+
+/// parse the time, rank and score fields for a part, which are all `-` if it hasn't been solved
+fn parse_part(fields: &[&str]) -> Option<PartStats> {
+    let time = *fields.first()?;
+    if time == "-" {
+        return None;
+    }
+    Some(PartStats {
+        time: parse_time(time),
+        rank: fields.get(1).and_then(|r| r.parse().ok()),
+        score: fields.get(2).and_then(|s| s.parse().ok()),
+    })
+}
+
+/// given the raw html of /YEAR/leaderboard/self, read the row for each day from the table of stats
+/// rows have a time, rank and score for each part, or just a time for each part in years without a global leaderboard
+pub fn parse_page(year: u16, page: &str) -> YearStats {
+    const TAG_PATTERN: &str = r"<[^>]*>";
+    const ROW_PATTERN: &str = r"(?m)^\s*(\d{1,2})\s+(\S.*?)\s*$";
+    let tags = Regex::new(TAG_PATTERN).expect("couldn't make regex");
+    let rows = Regex::new(ROW_PATTERN).expect("couldn't make regex");
+
+    // the table is in the only <pre> on the page
+    let table = page
+        .split_once("<pre")
+        .and_then(|(_, rest)| rest.split_once("</pre>"))
+        .map_or("", |(table, _)| table);
+    let text = tags.replace_all(table, "");
+
+    let mut days: Vec<DayStats> = rows
+        .captures_iter(&text)
+        .filter_map(|c| {
+            let day = c[1].parse().ok()?;
+            let fields: Vec<&str> = c[2].split_whitespace().collect();
+            let (part1, part2) = match fields.len() {
+                6 => (parse_part(&fields[..3]), parse_part(&fields[3..])),
+                3 => (parse_part(&fields), None),
+                2 => (parse_part(&fields[..1]), parse_part(&fields[1..])),
+                1 => (parse_part(&fields), None),
+                _ => return None,
+            };
+            Some(DayStats { day, part1, part2 })
+        })
+        .collect();
+    days.sort_by_key(|d| d.day);
+
+    YearStats { year, days }
+}
+
+/// where personal stats are stored (~/.local/share/aoc_utils/personal/ on linux)
+/// each named account has its own stats in ~/.local/share/aoc_utils/accounts/ACCOUNT/personal/
+fn stats_dir(account: Option<&str>) -> Option<PathBuf> {
+    let mut path = dirs::data_dir()?;
+    path.push("aoc_utils");
+    if let Some(account) = account {
+        path.push("accounts");
+        path.push(account);
+    }
+    path.push("personal");
+    Some(path)
+}
+
+/// store a year's stats, replacing what was stored before
+pub fn store(account: Option<&str>, stats: &YearStats) -> Result<(), PersonalError> {
+    let dir = stats_dir(account).ok_or(PersonalError::NoDataDir)?;
+    fs::create_dir_all(&dir).map_err(|e| PersonalError::DirCreationError(dir.clone(), e))?;
+    let path = dir.join(format!("{}.json", stats.year));
+    let json = serde_json::to_string_pretty(stats).expect("couldn't serialize stats");
+    fs::write(&path, json).map_err(|e| PersonalError::WriteError(path, e))
+}
+
+/// every year's stored stats, in order (nothing has been stored if the directory doesn't exist)
+pub fn load_all(account: Option<&str>) -> Result<Vec<YearStats>, PersonalError> {
+    let dir = stats_dir(account).ok_or(PersonalError::NoDataDir)?;
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(PersonalError::ReadError(dir, e)),
+    };
+
+    let mut all = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|e| PersonalError::ReadError(dir.clone(), e))?
+            .path();
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let json =
+            fs::read_to_string(&path).map_err(|e| PersonalError::ReadError(path.clone(), e))?;
+        all.push(serde_json::from_str(&json).map_err(|e| PersonalError::ParseError(path, e))?);
+    }
+    all.sort_by_key(|s: &YearStats| s.year);
+    Ok(all)
+}
+
+/// a part's time, or >24h like the site shows it
+fn format_time(part: &Option<PartStats>) -> String {
+    match part {
+        Some(PartStats {
+            time: Some(time), ..
+        }) => format_seconds(Some(i64::from(*time))),
+        Some(_) => ">24h".to_string(),
+        None => String::new(),
+    }
+}
+
+fn format_field(field: Option<u32>) -> String {
+    field.map(|f| f.to_string()).unwrap_or_default()
+}
+
+/// a row per day with the time, rank and score for each part
+pub fn format_year(stats: &YearStats) -> String {
+    let rows = stats
+        .days
+        .iter()
+        .map(|d| {
+            vec![
+                d.day.to_string(),
+                format_time(&d.part1),
+                format_field(d.part1.as_ref().and_then(|p| p.rank)),
+                format_field(d.part1.as_ref().and_then(|p| p.score)),
+                format_time(&d.part2),
+                format_field(d.part2.as_ref().and_then(|p| p.rank)),
+                // the last column is left aligned, so pad the score to line up with the others
+                format!(
+                    "{:>5}",
+                    format_field(d.part2.as_ref().and_then(|p| p.score))
+                ),
+            ]
+        })
+        .collect();
+    let table = table(
+        &[
+            "day",
+            "part 1 time",
+            "rank",
+            "score",
+            "part 2 time",
+            "rank",
+            "score",
+        ],
+        rows,
+    );
+    format!("{}\n{table}", stats.year)
+}
+
+/// a row per year with the stars, median times, best rank and total score, to see how they change over time
+pub fn format_trends(all: &[YearStats]) -> String {
+    let rows = all
+        .iter()
+        .map(|s| {
+            vec![
+                s.year.to_string(),
+                s.stars().to_string(),
+                format_seconds(median(s.times(1))),
+                format_seconds(median(s.times(2))),
+                format_field(s.best_rank()),
+                format!("{:>5}", s.score()),
+            ]
+        })
+        .collect();
+    table(
+        &[
+            "year",
+            "stars",
+            "median part 1",
+            "median part 2",
+            "best rank",
+            "score",
+        ],
+        rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// trimmed copies of /YEAR/leaderboard/self, with ranks and scores and with only times
+    const PERSONAL: &str = include_str!("../fixtures/personal.html");
+    const PERSONAL_TIMES_ONLY: &str = include_str!("../fixtures/personal_times_only.html");
+
+    /// the time, rank and score for a part, or None if it wasn't solved
+    fn fields(part: &Option<PartStats>) -> Option<(Option<u32>, Option<u32>, Option<u32>)> {
+        part.as_ref().map(|p| (p.time, p.rank, p.score))
+    }
+
+    #[test]
+    fn times_ranks_and_scores() {
+        let stats = parse_page(2019, PERSONAL);
+        assert_eq!(stats.year, 2019);
+        let days: Vec<u8> = stats.days.iter().map(|d| d.day).collect();
+        assert_eq!(days, vec![1, 2, 3, 25]);
+
+        let day = |n: usize| &stats.days[n];
+        assert_eq!(
+            fields(&day(0).part1),
+            Some((Some(3723), Some(5000), Some(0)))
+        );
+        assert_eq!(fields(&day(0).part2), Some((None, Some(20000), Some(0))));
+        assert_eq!(fields(&day(1).part1), Some((Some(192), Some(42), Some(59))));
+        assert_eq!(fields(&day(1).part2), Some((Some(300), Some(80), Some(21))));
+        assert_eq!(fields(&day(3).part1), Some((None, Some(12345), Some(0))));
+        assert_eq!(fields(&day(3).part2), None);
+
+        assert_eq!(stats.stars(), 7);
+        assert_eq!(stats.times(1), vec![3723, 192, 754]);
+        assert_eq!(stats.times(2), vec![300, 1201]);
+        assert_eq!(stats.best_rank(), Some(42));
+        assert_eq!(stats.score(), 80);
+    }
+
+    #[test]
+    fn times_only() {
+        let stats = parse_page(2025, PERSONAL_TIMES_ONLY);
+        let days: Vec<u8> = stats.days.iter().map(|d| d.day).collect();
+        assert_eq!(days, vec![1, 2]);
+        assert_eq!(fields(&stats.days[0].part1), Some((Some(600), None, None)));
+        assert_eq!(fields(&stats.days[0].part2), Some((Some(930), None, None)));
+        assert_eq!(fields(&stats.days[1].part1), Some((Some(2700), None, None)));
+        assert_eq!(fields(&stats.days[1].part2), None);
+        assert_eq!(stats.best_rank(), None);
+        assert_eq!(stats.score(), 0);
+    }
+
+    #[test]
+    fn no_table() {
+        let stats = parse_page(2019, "<main><p>You haven't collected any stars.</p></main>");
+        assert!(stats.days.is_empty());
+        assert_eq!(stats.stars(), 0);
+    }
+}
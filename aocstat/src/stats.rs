@@ -0,0 +1,415 @@
+use std::collections::{BTreeMap, HashMap};
+use std::iter;
+
+extern crate chrono;
+use chrono::{TimeZone, Utc};
+
+extern crate aocfetch;
+use aocfetch::fetches::FetchLog;
+
+use crate::leaderboard::{Leaderboard, Member};
+
+/// when each local member first fetched each day's input, as unix timestamps by member id then day
+pub type FirstFetches = HashMap<u64, BTreeMap<u8, i64>>;
+
+/// how a member did on a single day, with times in seconds since the puzzle unlocked
+pub struct DayResult {
+    pub id: u64,
+    pub name: String,
+    pub part1: Option<i64>,
+    pub part2: Option<i64>,
+    /// the time between the two stars, which doesn't depend on when the member started
+    pub delta: Option<i64>,
+    /// the time from when the member first fetched the input to part 2, for local members
+    pub since_fetch: Option<i64>,
+}
+
+/// how a member did over the whole year
+pub struct MemberStats {
+    pub id: u64,
+    pub name: String,
+    pub stars: u32,
+    pub local_score: u32,
+    pub fair_score: u32,
+    /// only local members, whose first fetches are known, have a fetch score
+    pub fetch_score: Option<u32>,
+    pub median_part1: Option<i64>,
+    pub median_part2: Option<i64>,
+    pub median_delta: Option<i64>,
+    pub median_since_fetch: Option<i64>,
+}
+
+/// when the day's puzzle unlocked (midnight UTC-5), as a unix timestamp
+fn unlock_ts(year: u16, day: u8) -> i64 {
+    Utc.with_ymd_and_hms(year as i32, 12, day as u32, 5, 0, 0)
+        .unwrap()
+        .timestamp()
+}
+
+/// the first fetches aocfetch recorded for each local account, matched to the leaderboard member with the same name
+pub fn first_fetches(board: &Leaderboard, year: u16, logs: &[FetchLog]) -> FirstFetches {
+    let ids: HashMap<String, u64> = board
+        .members
+        .values()
+        .map(|m| (m.display_name(), m.id))
+        .collect();
+    let mut fetches = FirstFetches::new();
+    for log in logs {
+        for ((_, day), fetch) in log.iter().filter(|((y, _), _)| *y == year) {
+            if let Some(&id) = ids.get(&fetch.user) {
+                fetches.entry(id).or_default().insert(day, fetch.ts);
+            }
+        }
+    }
+    fetches
+}
+
+fn day_result(member: &Member, year: u16, day: u8, fetches: &FirstFetches) -> DayResult {
+    let unlock = unlock_ts(year, day);
+    let part1 = member.star_ts(day, 1).map(|ts| ts - unlock);
+    let part2 = member.star_ts(day, 2).map(|ts| ts - unlock);
+    // an input fetched after part 1 was solved (e.g. on another computer) says nothing about when they started
+    let fetched = fetches.get(&member.id).and_then(|days| days.get(&day));
+    let since_fetch = fetched
+        .filter(|&&fetched| member.star_ts(day, 1).is_some_and(|ts| ts >= fetched))
+        .zip(member.star_ts(day, 2))
+        .map(|(fetched, ts)| ts - fetched);
+    DayResult {
+        id: member.id,
+        name: member.display_name(),
+        part1,
+        part2,
+        delta: part1.zip(part2).map(|(p1, p2)| p2 - p1),
+        since_fetch,
+    }
+}
+
+/// how every member with a star on the day did, fastest part 2 delta first, then anyone who only has part 1
+pub fn day_results(
+    board: &Leaderboard,
+    year: u16,
+    day: u8,
+    fetches: &FirstFetches,
+) -> Vec<DayResult> {
+    let mut results: Vec<DayResult> = board
+        .members
+        .values()
+        .filter(|m| m.stars_on(day) > 0)
+        .map(|m| day_result(m, year, day, fetches))
+        .collect();
+    results.sort_by_key(|r| (r.delta.is_none(), r.delta, r.part1, r.id));
+    results
+}
+
+/// the middle value, or the mean of the middle two if there's an even number of them
+pub(crate) fn median(mut values: Vec<i64>) -> Option<i64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        Some((values[mid - 1] + values[mid]) / 2)
+    } else {
+        Some(values[mid])
+    }
+}
+
+/// every member's stats for the year, highest fair score first
+/// the fair score works like the local score, but each day is ranked by part 2 delta rather than by when part 2
+/// was solved, so members who can't start at midnight UTC-5 aren't punished for it
+/// the fetch score ranks each day by the time from first fetching the input to part 2 instead, which counts
+/// part 1 too, but it's only known for local members since adventofcode.com doesn't say when anyone else started
+pub fn member_stats(board: &Leaderboard, year: u16, fetches: &FirstFetches) -> Vec<MemberStats> {
+    let members = board.members.len() as u32;
+    let mut fair_scores = HashMap::new();
+    let mut fetch_scores = HashMap::new();
+    for day in 1..=board.last_day() {
+        let mut ranked = day_results(board, year, day, fetches);
+        for (rank, result) in ranked.iter().filter(|r| r.delta.is_some()).enumerate() {
+            *fair_scores.entry(result.id).or_insert(0) += members - rank as u32;
+        }
+        ranked.retain(|r| r.since_fetch.is_some());
+        ranked.sort_by_key(|r| (r.since_fetch, r.id));
+        for (rank, result) in ranked.iter().enumerate() {
+            *fetch_scores.entry(result.id).or_insert(0) += members - rank as u32;
+        }
+    }
+
+    let mut stats: Vec<MemberStats> = board
+        .members
+        .values()
+        .map(|member| {
+            let days: Vec<DayResult> = member
+                .completion_day_level
+                .keys()
+                .map(|&day| day_result(member, year, day, fetches))
+                .collect();
+            let fetch_score = fetches
+                .contains_key(&member.id)
+                .then(|| fetch_scores.get(&member.id).copied().unwrap_or(0));
+            MemberStats {
+                id: member.id,
+                name: member.display_name(),
+                stars: member.stars,
+                local_score: member.local_score,
+                fair_score: fair_scores.get(&member.id).copied().unwrap_or(0),
+                fetch_score,
+                median_part1: median(days.iter().filter_map(|d| d.part1).collect()),
+                median_part2: median(days.iter().filter_map(|d| d.part2).collect()),
+                median_delta: median(days.iter().filter_map(|d| d.delta).collect()),
+                median_since_fetch: median(days.iter().filter_map(|d| d.since_fetch).collect()),
+            }
+        })
+        .collect();
+    stats.sort_by_key(|s| (u32::MAX - s.fair_score, u32::MAX - s.local_score, s.id));
+    stats
+}
+
+/// a number of seconds as hours, minutes and seconds, e.g. 27:03:09
+pub(crate) fn format_seconds(seconds: Option<i64>) -> String {
+    match seconds {
+        Some(s) => format!("{}:{:02}:{:02}", s / 3600, s % 3600 / 60, s % 60),
+        None => String::new(),
+    }
+}
+
+/// a number of seconds as a plain number for csv
+fn csv_seconds(seconds: Option<i64>) -> String {
+    seconds.map(|s| s.to_string()).unwrap_or_default()
+}
+
+/// quote a csv field if it needs it
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// a table with every column right aligned except the last, which is meant for names
+pub(crate) fn table(headers: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = headers.iter().map(|h| h.chars().count()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header = headers.iter().map(|h| h.to_string()).collect();
+    iter::once(header)
+        .chain(rows)
+        .map(|row| {
+            let last = row.len() - 1;
+            row.iter()
+                .enumerate()
+                .map(|(i, cell)| {
+                    if i == last {
+                        cell.clone()
+                    } else {
+                        format!("{cell:>width$}", width = widths[i])
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// every member's stats for the year as a table or csv, with times in seconds for csv
+pub fn format_member_stats(stats: &[MemberStats], csv: bool) -> String {
+    if csv {
+        let mut lines = vec![
+            "id,name,stars,local_score,fair_score,fetch_score,median_part1,median_part2,median_delta,median_since_fetch"
+                .to_string(),
+        ];
+        lines.extend(stats.iter().map(|s| {
+            format!(
+                "{},{},{},{},{},{},{},{},{},{}",
+                s.id,
+                csv_field(&s.name),
+                s.stars,
+                s.local_score,
+                s.fair_score,
+                s.fetch_score.map(|f| f.to_string()).unwrap_or_default(),
+                csv_seconds(s.median_part1),
+                csv_seconds(s.median_part2),
+                csv_seconds(s.median_delta),
+                csv_seconds(s.median_since_fetch)
+            )
+        }));
+        return lines.join("\n");
+    }
+
+    let rows = stats
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            vec![
+                (i + 1).to_string(),
+                s.fair_score.to_string(),
+                s.fetch_score.map(|f| f.to_string()).unwrap_or_default(),
+                s.local_score.to_string(),
+                s.stars.to_string(),
+                format_seconds(s.median_part1),
+                format_seconds(s.median_part2),
+                format_seconds(s.median_delta),
+                format_seconds(s.median_since_fetch),
+                s.name.clone(),
+            ]
+        })
+        .collect();
+    table(
+        &[
+            "rank",
+            "fair score",
+            "fetch score",
+            "local score",
+            "stars",
+            "median part 1",
+            "median part 2",
+            "median delta",
+            "median since fetch",
+            "name",
+        ],
+        rows,
+    )
+}
+
+/// how every member did on a day as a table or csv, with times in seconds for csv
+pub fn format_day_results(results: &[DayResult], csv: bool) -> String {
+    if csv {
+        let mut lines = vec!["id,name,part1,part2,delta,since_fetch".to_string()];
+        lines.extend(results.iter().map(|r| {
+            format!(
+                "{},{},{},{},{},{}",
+                r.id,
+                csv_field(&r.name),
+                csv_seconds(r.part1),
+                csv_seconds(r.part2),
+                csv_seconds(r.delta),
+                csv_seconds(r.since_fetch)
+            )
+        }));
+        return lines.join("\n");
+    }
+
+    let rows = results
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            vec![
+                (i + 1).to_string(),
+                format_seconds(r.delta),
+                format_seconds(r.part1),
+                format_seconds(r.part2),
+                format_seconds(r.since_fetch),
+                r.name.clone(),
+            ]
+        })
+        .collect();
+    table(
+        &["rank", "delta", "part 1", "part 2", "since fetch", "name"],
+        rows,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    extern crate tempfile;
+
+    /// when day 1 of 2023 unlocked
+    const UNLOCK: i64 = 1701406800;
+
+    /// a leaderboard where everyone has both stars on day 1
+    /// alice and bob are local, carol is anonymous and dave fetched his input after solving part 1
+    fn board() -> Leaderboard {
+        let member = |id: u64, name: &str, part1: i64, part2: i64| {
+            format!(
+                r#""{id}": {{"id": {id}, "name": {name}, "stars": 2, "local_score": 0, "last_star_ts": {},
+                "completion_day_level": {{"1": {{"1": {{"get_star_ts": {}}}, "2": {{"get_star_ts": {}}}}}}}}}"#,
+                UNLOCK + part2,
+                UNLOCK + part1,
+                UNLOCK + part2
+            )
+        };
+        let members = [
+            member(1, r#""alice""#, 4000, 4500),
+            member(2, r#""bob""#, 100, 1000),
+            member(3, "null", 50, 60),
+            member(4, r#""dave""#, 200, 300),
+        ];
+        Leaderboard::parse(&format!(r#"{{"members": {{{}}}}}"#, members.join(","))).unwrap()
+    }
+
+    /// first fetch logs for the local accounts, as aocfetch would have saved them
+    fn logs(dir: &std::path::Path) -> Vec<FetchLog> {
+        let fetches = [
+            ("alice", 2023, UNLOCK + 3600),
+            ("bob", 2023, UNLOCK),
+            ("dave", 2023, UNLOCK + 250),
+            // not on this leaderboard, and a different year
+            ("erin", 2023, UNLOCK),
+            ("alice", 2022, UNLOCK),
+        ];
+        fetches
+            .iter()
+            .enumerate()
+            .map(|(i, &(user, year, ts))| {
+                let mut log = FetchLog::open_at(dir.join(format!("{i}.tsv"))).unwrap();
+                log.record(year, 1, ts, user);
+                log
+            })
+            .collect()
+    }
+
+    #[test]
+    fn first_fetches_match_local_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let fetches = first_fetches(&board(), 2023, &logs(dir.path()));
+        let mut ids: Vec<u64> = fetches.keys().copied().collect();
+        ids.sort();
+        assert_eq!(ids, vec![1, 2, 4]);
+        assert_eq!(fetches[&1][&1], UNLOCK + 3600);
+
+        let results = day_results(&board(), 2023, 1, &fetches);
+        let since_fetch: Vec<(u64, Option<i64>)> =
+            results.iter().map(|r| (r.id, r.since_fetch)).collect();
+        // ranked by delta, with no time since fetch for members who aren't local or fetched too late
+        assert_eq!(
+            since_fetch,
+            vec![(3, None), (4, None), (1, Some(900)), (2, Some(1000))]
+        );
+    }
+
+    #[test]
+    fn fetch_score_ranks_local_members() {
+        let dir = tempfile::tempdir().unwrap();
+        let board = board();
+        let fetches = first_fetches(&board, 2023, &logs(dir.path()));
+        let scores: Vec<(u64, u32, Option<u32>, Option<i64>)> =
+            member_stats(&board, 2023, &fetches)
+                .iter()
+                .map(|s| (s.id, s.fair_score, s.fetch_score, s.median_since_fetch))
+                .collect();
+        assert_eq!(
+            scores,
+            vec![
+                (3, 4, None, None),
+                (4, 3, Some(0), None),
+                (1, 2, Some(4), Some(900)),
+                (2, 1, Some(3), Some(1000)),
+            ]
+        );
+
+        // without any first fetches, nobody has a fetch score
+        let stats = member_stats(&board, 2023, &FirstFetches::new());
+        assert!(stats.iter().all(|s| s.fetch_score.is_none()));
+    }
+}
@@ -1,9 +1,10 @@
 use std::io::{self, Write};
-use std::path::PathBuf;
+use std::thread;
+use std::time::Duration;
 
 extern crate clap;
 use clap::error::ErrorKind;
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, Parser, Subcommand};
 
 extern crate chrono;
 use chrono::{DateTime, Datelike, FixedOffset, Utc};
@@ -11,13 +12,17 @@ use chrono::{DateTime, Datelike, FixedOffset, Utc};
 extern crate thiserror;
 use thiserror::Error;
 
-extern crate dirs;
-
 extern crate aocfetch;
-use aocfetch::session::{self, SessionError};
+use aocfetch::answers::{AnswerError, AnswerStore};
+use aocfetch::report::{self, Puzzle, Report};
+use aocfetch::request::{self as fetch_request, RequestError as FetchRequestError};
+use aocfetch::session::{
+    parse_account, SecretServiceKeyring, SessionArgs, SessionConfig, SessionError,
+};
 
 mod request;
 use request::RequestError;
+pub use request::Verdict;
 
 #[derive(Parser)]
 #[command(name = "aocsub")]
@@ -27,89 +32,105 @@ use request::RequestError;
     about = "A command line utility to submit answers for Advent of Code <https://adventofcode.com> puzzles"
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// the answer to submit (defaults to stdin)
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     answer: Option<String>,
 
-    /// your adventofcode.com session cookie
-    #[arg(group = "session", short, long)]
-    cookie: Option<String>,
-    /// a file containing your adventofcode.com session cookie
-    #[arg(group = "session", short, long)]
-    file: Option<PathBuf>,
-    /// the location of your firefox dotfiles (defaults to ~/.mozilla/firefox)
-    // because of the mutual exclusivity with the other session args, we'll handle the default in Config::make
-    #[arg(group = "session", short, long)]
-    browser_folder: Option<PathBuf>,
+    #[command(flatten)]
+    session: SessionArgs,
 
     /// the day to submit the answer for
     /// (defaults to current day if UTC-5 is December, otherwise 1)
-    #[arg(value_parser = clap::value_parser!(u8).range(1..=31), short, long)]
+    #[arg(value_parser = clap::value_parser!(u8).range(1..=31), short, long, global = true)]
     day: Option<u8>,
     /// the year to submit the answer for
     /// (defaults to current year if UTC-5 is December, otherwise the previous year)
     /// NOTE: this will break in the year 65,536. File a github issue if you encounter this.
-    #[arg(value_parser = clap::value_parser!(u16).range(2015..), short, long)]
+    #[arg(value_parser = clap::value_parser!(u16).range(2015..), short, long, global = true)]
     year: Option<u16>, // we'll validate this as a year that isn't in the future in the make function
 
     /// the level to submit the answer for (1 or 2, defaults to 1)
-    #[arg(default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2), short, long)]
+    #[arg(default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=2), short, long, global = true)]
     level: u8,
+
+    /// report where the session cookie came from on stderr
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// print a json object describing what happened instead of plain text
+    #[arg(long, global = true)]
+    json: bool,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// check the answer against the recorded correct answer instead of submitting it
+    Verify,
+    /// record the answers to puzzles you've already solved, as shown on the puzzle pages
+    /// (every unlocked day of the year, or just the one given with --day)
+    Backfill,
 }
 
 /// configuration options for the app created based on cli args
 pub struct Config {
+    action: Action,
     session_cfg: SessionConfig,
+    /// the named account, or None for the unnamed one
+    account: Option<String>,
+    verbose: bool,
+    json: bool,
     day: u8,
     year: u16,
     level: u8,
     answer: String,
 }
 
-/// keep track of how the application will get the session cookie, inferred from the cli args
-enum SessionConfig {
-    Direct(String),
-    File(PathBuf),
-    Firefox(PathBuf),
+/// keep track of what the application will do with the answer
+enum Action {
+    Submit,
+    Verify,
+    Backfill(Vec<u8>),
 }
 
 /// construct app config from arguments
 impl Config {
+    /// construct config for submitting an answer from another program (e.g. a puzzle solution)
+    /// the session cookie is found the same way as on the command line without any session args:
+    /// from AOC_SESSION or AOC_SESSION_FILE, then the keyring, then the default firefox profile
+    pub fn new(year: u16, day: u8, level: u8, answer: String) -> Self {
+        Config {
+            action: Action::Submit,
+            session_cfg: SessionConfig::from_env()
+                .unwrap_or_else(|| SessionConfig::for_account(None)),
+            account: None,
+            verbose: false,
+            json: false,
+            day,
+            year,
+            level,
+            answer,
+        }
+    }
+
+    /// submit with a named account's cookie from the keyring and record the answer in its store,
+    /// e.g. for a solution with `account = "work"` in its [package.metadata.aoc] table
+    /// errors if the name isn't a valid account name
+    pub fn with_account(mut self, account: &str) -> Result<Self, String> {
+        let account = parse_account(account)?;
+        self.session_cfg = SessionConfig::for_account(Some(&account));
+        self.account = Some(account);
+        Ok(self)
+    }
+
     pub fn make() -> Self {
         let args = Args::parse();
 
-        // parse and store the answer
-        let answer = if let Some(ans) = args.answer {
-            ans
-        } else {
-            let mut buf = String::new();
-            io::stdin().read_line(&mut buf).unwrap_or_else(|_| {
-                let mut cmd = Args::command();
-                cmd.error(
-                    ErrorKind::InvalidValue,
-                    "no answer provided and it could not be parsed from stdin",
-                )
-                .exit();
-            });
-            buf
-        };
-
         // how will we get the session cookie?
-        let session_cfg = if let Some(session_string) = args.cookie {
-            // the user passed it directly
-            SessionConfig::Direct(session_string.clone())
-        } else if let Some(session_file) = args.file {
-            // the user stored it in a file
-            SessionConfig::File(session_file.clone())
-        } else if let Some(firefox_folder) = args.browser_folder {
-            // the user wants to grab it from firefox and provided the config folder
-            SessionConfig::Firefox(firefox_folder.clone())
-        } else {
-            // we default to grabbing it from where we assume the firefox config folder is
-            let mut firefox_folder = dirs::home_dir().unwrap();
-            firefox_folder.push(".mozilla/firefox");
-            SessionConfig::Firefox(firefox_folder)
-        };
+        let account = args.session.account();
+        let session_cfg = SessionConfig::from(args.session);
 
         // time sensitive config
         let dt = get_aoc_time();
@@ -154,8 +175,48 @@ impl Config {
 
         let level = args.level;
 
+        let action = match args.command {
+            Some(Command::Verify) => Action::Verify,
+            Some(Command::Backfill) => {
+                // only the days that have been unlocked so far
+                let days = if let Some(arg_day) = args.day {
+                    vec![arg_day]
+                } else if year == dt.year() as u16 && dt.month() == 12 {
+                    (1..=dt.day().min(25) as u8).collect()
+                } else if year == dt.year() as u16 {
+                    Vec::new()
+                } else {
+                    (1..=25).collect()
+                };
+                Action::Backfill(days)
+            }
+            None => Action::Submit,
+        };
+
+        // parse and store the answer, which backfilling doesn't need
+        let answer = if let Action::Backfill(_) = action {
+            String::new()
+        } else if let Some(ans) = args.answer {
+            ans
+        } else {
+            let mut buf = String::new();
+            io::stdin().read_line(&mut buf).unwrap_or_else(|_| {
+                let mut cmd = Args::command();
+                cmd.error(
+                    ErrorKind::InvalidValue,
+                    "no answer provided and it could not be parsed from stdin",
+                )
+                .exit();
+            });
+            buf
+        };
+
         Config {
+            action,
             session_cfg,
+            account,
+            verbose: args.verbose,
+            json: args.json,
             day,
             year,
             level,
@@ -164,6 +225,17 @@ impl Config {
     }
 }
 
+impl Config {
+    /// figure out the session cookie, reporting where it really came from if asked to
+    fn session_cookie(&self) -> Result<String, SessionError> {
+        let session = self.session_cfg.resolve_with(&SecretServiceKeyring)?;
+        if self.verbose {
+            eprintln!("using the session cookie from {}", session.source);
+        }
+        Ok(session.cookie)
+    }
+}
+
 /// return a DateTime struct representing the current time for AOC
 pub fn get_aoc_time() -> DateTime<Utc> {
     // seconds in an hour
@@ -181,23 +253,116 @@ pub fn get_aoc_time() -> DateTime<Utc> {
 pub enum RunError {
     #[error("error retrieving session cookie: {0}")]
     SessionError(#[from] SessionError),
-    #[error("error occurred while posting answer to adventofcode.com: {0}")]
+    #[error("error checking the session cookie: {0}")]
+    SessionCheckError(#[from] FetchRequestError),
+    #[error("error occurred while making a request to adventofcode.com: {0}")]
     RequestError(#[from] RequestError),
     #[error("error occured while attempting to write to stdout: {0}")]
     StdoutError(io::Error),
+    #[error("the answer was accepted but couldn't be recorded: {0}")]
+    RecordError(AnswerError),
+    #[error("error occured while loading or saving recorded answers: {0}")]
+    AnswerError(#[from] AnswerError),
+    #[error("that's not the right answer, the recorded answer is {0}")]
+    IncorrectAnswer(String),
+    #[error("{0}")]
+    RejectedAnswer(String),
+    #[error("{0}")]
+    TooRecent(String),
+    #[error("{0}")]
+    WrongLevel(String),
+    #[error("unrecognized response from adventofcode.com: {0}")]
+    UnknownVerdict(String),
+    #[error("there's no recorded answer for {0} day {1} level {2}")]
+    UnrecordedAnswer(u16, u8, u8),
+}
+
+impl RunError {
+    /// what kind of problem this is, for --json
+    pub fn kind(&self) -> report::ErrorKind {
+        match self {
+            RunError::SessionError(e) => e.kind(),
+            RunError::SessionCheckError(e) => e.kind(),
+            RunError::RequestError(e) => e.kind(),
+            RunError::StdoutError(_) | RunError::RecordError(_) | RunError::AnswerError(_) => {
+                report::ErrorKind::Io
+            }
+            RunError::IncorrectAnswer(_) | RunError::RejectedAnswer(_) => {
+                report::ErrorKind::WrongAnswer
+            }
+            RunError::TooRecent(_) => report::ErrorKind::RateLimited,
+            RunError::WrongLevel(_)
+            | RunError::UnknownVerdict(_)
+            | RunError::UnrecordedAnswer(_, _, _) => report::ErrorKind::Other,
+        }
+    }
+
+    /// anything but a correct answer is an error as far as the exit code is concerned
+    fn from_verdict(verdict: Verdict, message: String) -> Result<String, Self> {
+        match verdict {
+            Verdict::Correct => Ok(message),
+            Verdict::Incorrect => Err(RunError::RejectedAnswer(message)),
+            Verdict::TooRecent => Err(RunError::TooRecent(message)),
+            Verdict::WrongLevel => Err(RunError::WrongLevel(message)),
+            Verdict::Unknown => Err(RunError::UnknownVerdict(message)),
+        }
+    }
 }
 
 /// run the application according to the provided config
 pub fn run(cfg: Config) -> Result<(), RunError> {
-    // figure out the session cookie
-    let session_cookie = match cfg.session_cfg {
-        SessionConfig::Direct(session_string) => session_string,
-        SessionConfig::File(file) => session::from_file(file)?,
-        SessionConfig::Firefox(folder) => session::from_firefox(folder)?,
+    let (action, day) = match cfg.action {
+        Action::Submit => ("submit", Some(cfg.day)),
+        Action::Verify => ("verify", Some(cfg.day)),
+        // backfilling a whole year isn't about any one day
+        Action::Backfill(ref days) => ("backfill", (days.len() == 1).then_some(cfg.day)),
     };
+    let puzzle = Puzzle {
+        year: cfg.year,
+        day,
+        level: day.and(Some(cfg.level)),
+    };
+    let mut report = Report::new(action, Some(puzzle), cfg.account.clone());
 
-    let mut recv =
-        request::post_answer(cfg.year, cfg.day, cfg.level, &cfg.answer, &session_cookie)?;
+    let result = match cfg.action {
+        Action::Submit => post(&cfg, &mut report)
+            .and_then(|(verdict, recv)| RunError::from_verdict(verdict, recv)),
+        Action::Backfill(ref days) => backfill(&cfg, days, &mut report),
+        Action::Verify => match verify(
+            cfg.account.as_deref(),
+            cfg.year,
+            cfg.day,
+            cfg.level,
+            &cfg.answer,
+        ) {
+            Ok(Verification::Correct) => Ok("That's the right answer!".to_string()),
+            Ok(Verification::Incorrect(expected)) => Err(RunError::IncorrectAnswer(expected)),
+            Ok(Verification::Unrecorded) => {
+                Err(RunError::UnrecordedAnswer(cfg.year, cfg.day, cfg.level))
+            }
+            Err(e) => Err(e.into()),
+        },
+    };
+
+    if cfg.json {
+        report.finish(
+            result
+                .as_ref()
+                .map_or(String::new(), |recv| format!("{recv}\n")),
+            result.as_ref().err().map(|e| (e.kind(), e.to_string())),
+        );
+        io::stdout()
+            .write_all(format!("{}\n", report.to_json()).as_bytes())
+            .map_err(RunError::StdoutError)?;
+        return result.map(|_| ());
+    }
+
+    // every line of output is about this account
+    let mut recv = match &cfg.account {
+        Some(account) => format!("account {account}\n"),
+        None => String::new(),
+    };
+    recv.push_str(&result?);
     recv.push('\n');
 
     io::stdout()
@@ -206,3 +371,167 @@ pub fn run(cfg: Config) -> Result<(), RunError> {
 
     Ok(())
 }
+
+/// submit the answer according to the provided config and return the server's verdict
+pub fn submit(cfg: Config) -> Result<String, RunError> {
+    // only --json needs the details
+    let mut report = Report::new("submit", None, None);
+    post(&cfg, &mut report).map(|(_, message)| message)
+}
+
+/// submit the answer, noting the verdict, how long to wait and where it was recorded in the report
+/// returns the verdict along with the server's message
+fn post(cfg: &Config, report: &mut Report) -> Result<(Verdict, String), RunError> {
+    let session_cookie = cfg.session_cookie()?;
+    // an expired cookie gets an unhelpful error code, so make sure it's logged in first
+    fetch_request::check_session(&session_cookie)?;
+
+    let submission =
+        request::post_answer(cfg.year, cfg.day, cfg.level, &cfg.answer, &session_cookie)?;
+    let verdict = Verdict::from_message(&submission.message);
+    report.outcome = verdict.as_str().to_string();
+    report.wait_seconds = submission.wait.map(|wait| wait.as_secs());
+
+    // remember accepted answers so solutions can be verified offline later
+    if verdict == Verdict::Correct {
+        let mut store = AnswerStore::open(cfg.account.as_deref()).map_err(RunError::RecordError)?;
+        store.insert(cfg.year, cfg.day, cfg.level, &cfg.answer);
+        store.save().map_err(RunError::RecordError)?;
+        report.paths.push(store.path().to_path_buf());
+    }
+
+    Ok((verdict, submission.message))
+}
+
+/// fetch the puzzle pages for the days and record the answers shown for the levels already solved
+/// returns a line for each recorded answer
+fn backfill(cfg: &Config, days: &[u8], report: &mut Report) -> Result<String, RunError> {
+    // be polite to the server when fetching a whole year
+    const REQUEST_DELAY: Duration = Duration::from_millis(500);
+
+    let year = cfg.year;
+    let session_cookie = cfg.session_cookie()?;
+    fetch_request::check_session(&session_cookie)?;
+    let mut store = AnswerStore::open(cfg.account.as_deref())?;
+    let mut lines = Vec::new();
+
+    for (i, &day) in days.iter().enumerate() {
+        if i > 0 {
+            thread::sleep(REQUEST_DELAY);
+        }
+        let answers = match request::get_puzzle_answers(year, day, &session_cookie) {
+            Ok(answers) => answers,
+            // later years have fewer than 25 days
+            Err(RequestError::BadResponse(404)) => break,
+            Err(e) => {
+                // keep what we've found so far
+                store.save()?;
+                report.paths.push(store.path().to_path_buf());
+                return Err(e.into());
+            }
+        };
+        for (level, answer) in (1..).zip(answers) {
+            lines.push(format!("{year} day {day} level {level}: {answer}"));
+            store.insert(year, day, level, &answer);
+        }
+    }
+
+    store.save()?;
+    report.paths.push(store.path().to_path_buf());
+    lines.push(format!("recorded {} answer(s)", lines.len()));
+    Ok(lines.join("\n"))
+}
+
+/// the result of checking an answer against the recorded correct answer
+pub enum Verification {
+    Correct,
+    /// the answer is wrong, and this is the recorded correct one
+    Incorrect(String),
+    /// there's no recorded answer to check against
+    Unrecorded,
+}
+
+/// check an answer against the recorded correct answer for the puzzle without submitting it
+/// (each named account has its own recorded answers, None is the unnamed account)
+pub fn verify(
+    account: Option<&str>,
+    year: u16,
+    day: u8,
+    level: u8,
+    answer: &str,
+) -> Result<Verification, AnswerError> {
+    let store = AnswerStore::open(account)?;
+    Ok(match store.get(year, day, level) {
+        Some(expected) if expected == answer.trim() => Verification::Correct,
+        Some(expected) => Verification::Incorrect(expected.to_string()),
+        None => Verification::Unrecorded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_correct_verdicts_succeed() {
+        let message = || "the server's message".to_string();
+        assert_eq!(
+            RunError::from_verdict(Verdict::Correct, message()).unwrap(),
+            message()
+        );
+
+        let error = |verdict| RunError::from_verdict(verdict, message()).unwrap_err();
+        assert!(matches!(error(Verdict::Incorrect), RunError::RejectedAnswer(m) if m == message()));
+        assert_eq!(
+            error(Verdict::Incorrect).kind(),
+            report::ErrorKind::WrongAnswer
+        );
+        assert_eq!(
+            error(Verdict::TooRecent).kind(),
+            report::ErrorKind::RateLimited
+        );
+        assert_eq!(error(Verdict::WrongLevel).kind(), report::ErrorKind::Other);
+        assert_eq!(error(Verdict::Unknown).kind(), report::ErrorKind::Other);
+    }
+
+    #[test]
+    fn error_kinds() {
+        let kinds = [
+            (
+                RunError::SessionError(SessionError::MissingCookie),
+                report::ErrorKind::Session,
+            ),
+            (
+                RunError::SessionCheckError(FetchRequestError::LoggedOut),
+                report::ErrorKind::Session,
+            ),
+            (
+                RunError::RequestError(RequestError::BadResponse(404)),
+                report::ErrorKind::NotUnlocked,
+            ),
+            (
+                RunError::RequestError(RequestError::MissingApproval(String::new())),
+                report::ErrorKind::Other,
+            ),
+            (
+                RunError::StdoutError(io::Error::other("disk full")),
+                report::ErrorKind::Io,
+            ),
+            (
+                RunError::RecordError(AnswerError::NoDataDir),
+                report::ErrorKind::Io,
+            ),
+            (
+                RunError::IncorrectAnswer("42".to_string()),
+                report::ErrorKind::WrongAnswer,
+            ),
+            (
+                RunError::UnrecordedAnswer(2023, 1, 1),
+                report::ErrorKind::Other,
+            ),
+        ];
+        for (error, kind) in kinds {
+            assert_eq!(error.kind(), kind, "{error}");
+        }
+    }
+}
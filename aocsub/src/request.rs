@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
 extern crate thiserror;
 use thiserror::Error;
@@ -10,25 +11,88 @@ use reqwest::StatusCode;
 extern crate regex;
 use regex::Regex;
 
-/// an error encountered while posting the answer
+extern crate aocfetch;
+use aocfetch::report::ErrorKind;
+
+/// an error encountered while talking to adventofcode.com
 #[derive(Error, Debug)]
 pub enum RequestError {
     #[error("unable to complete request to {0}: {1}")]
     RequestFailed(String, reqwest::Error),
+    #[error("unable to read the response from {0}: {1}")]
+    UnreadableResponse(String, reqwest::Error),
     #[error("bad response from server with status code {0}")]
     BadResponse(u16),
     #[error("unable to find answer approval statement in response: {0}")]
     MissingApproval(String),
 }
 
-/// given the url and form params and a cookie, make a post request to submit the answer, return the resulting text or error
+impl RequestError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            RequestError::RequestFailed(_, _) | RequestError::UnreadableResponse(_, _) => {
+                ErrorKind::Network
+            }
+            RequestError::BadResponse(status) => ErrorKind::from_status(*status),
+            RequestError::MissingApproval(_) => ErrorKind::Other,
+        }
+    }
+}
+
+/// adventofcode.com's response to a submitted answer
+pub struct Submission {
+    /// the first sentence of the response, which says whether the answer was right
+    pub message: String,
+    /// how long it asked to wait before submitting another answer, if it did
+    pub wait: Option<Duration>,
+}
+
+/// what adventofcode.com thought of a submitted answer, based on the message in its response
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Correct,
+    Incorrect,
+    TooRecent,
+    WrongLevel,
+    Unknown,
+}
+
+impl Verdict {
+    /// classify the message returned by `post_answer`
+    pub fn from_message(message: &str) -> Self {
+        if message.starts_with("That's the right answer") {
+            Verdict::Correct
+        } else if message.starts_with("That's not the right answer") {
+            Verdict::Incorrect
+        } else if message.starts_with("You gave an answer too recently") {
+            Verdict::TooRecent
+        } else if message.starts_with("You don't seem to be solving the right level") {
+            Verdict::WrongLevel
+        } else {
+            Verdict::Unknown
+        }
+    }
+
+    /// the verdict as it's shown with --json
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Verdict::Correct => "correct",
+            Verdict::Incorrect => "incorrect",
+            Verdict::TooRecent => "too_recent",
+            Verdict::WrongLevel => "wrong_level",
+            Verdict::Unknown => "unknown",
+        }
+    }
+}
+
+/// given the url and form params and a cookie, make a post request to submit the answer, return the response or error
 pub fn post_answer(
     year: u16,
     day: u8,
     level: u8,
     answer: &str,
     session_cookie: &str,
-) -> Result<String, RequestError> {
+) -> Result<Submission, RequestError> {
     let url = format!("https://adventofcode.com/{year}/day/{day}/answer");
 
     let mut form_params = HashMap::new();
@@ -41,28 +105,191 @@ pub fn post_answer(
         .header("Cookie", format!("session={session_cookie}"))
         .form(&form_params)
         .send()
-        .map_err(|e| RequestError::RequestFailed(url, e))?;
+        .map_err(|e| RequestError::RequestFailed(url.clone(), e))?;
+
+    match response.status() {
+        StatusCode::OK => parse_response(
+            &response
+                .text()
+                .map_err(|e| RequestError::UnreadableResponse(url, e))?,
+        ),
+        other => Err(RequestError::BadResponse(other.as_u16())),
+    }
+}
+
+/// fetch a puzzle page with a cookie and return the answers it shows for the levels already solved, in order
+pub fn get_puzzle_answers(
+    year: u16,
+    day: u8,
+    session_cookie: &str,
+) -> Result<Vec<String>, RequestError> {
+    let url = format!("https://adventofcode.com/{year}/day/{day}");
+
+    let client = Client::new();
+    let response = client
+        .get(&url)
+        .header("Cookie", format!("session={session_cookie}"))
+        .send()
+        .map_err(|e| RequestError::RequestFailed(url.clone(), e))?;
 
     match response.status() {
-        StatusCode::OK => parse_response(&response.text().unwrap()),
+        StatusCode::OK => Ok(parse_puzzle_answers(
+            &response
+                .text()
+                .map_err(|e| RequestError::UnreadableResponse(url, e))?,
+        )),
         other => Err(RequestError::BadResponse(other.as_u16())),
     }
 }
 
-/// given the raw html from an ok response, return the relevant first sentence
-fn parse_response(resp: &str) -> Result<String, RequestError> {
+/// given the raw html of a puzzle page, return the contents of each "Your puzzle answer was" statement
+fn parse_puzzle_answers(page: &str) -> Vec<String> {
+    const PATTERN: &str = r"Your puzzle answer was <code>([^<]*)</code>";
+    let reg = Regex::new(PATTERN).expect("couldn't make regex");
+    reg.captures_iter(page)
+        .map(|c| c[1].trim().to_string())
+        .collect()
+}
+
+/// given the raw html from an ok response, return the relevant first sentence and how long to wait
+fn parse_response(resp: &str) -> Result<Submission, RequestError> {
     const PATTERN: &str = r"<article>\s*<p>[^\.]*\.";
     let reg = Regex::new(PATTERN).expect("couldn't make regex");
     if let Some(m) = reg.find(resp) {
-        Ok(m.as_str()
+        let message = m
+            .as_str()
             .strip_prefix("<article>")
             .unwrap()
             .trim()
             .strip_prefix("<p>")
             .unwrap()
             .trim()
-            .to_string())
+            .to_string();
+        Ok(Submission {
+            message,
+            wait: parse_wait(resp),
+        })
     } else {
         Err(RequestError::MissingApproval(resp.to_string()))
     }
 }
+
+/// given the raw html from an ok response, find how long it says to wait before submitting again
+/// answering too soon says "You have 1m 23s left to wait", a wrong answer says "please wait 5 minutes before trying again"
+fn parse_wait(resp: &str) -> Option<Duration> {
+    const LEFT_PATTERN: &str = r"You have (?:(\d+)m )?(\d+)s left to wait";
+    const MINUTES_PATTERN: &str = r"wait (one|\d+) minutes? before trying again";
+    let left = Regex::new(LEFT_PATTERN).expect("couldn't make regex");
+    let minutes = Regex::new(MINUTES_PATTERN).expect("couldn't make regex");
+
+    if let Some(c) = left.captures(resp) {
+        let m: u64 = c.get(1).map_or(Some(0), |m| m.as_str().parse().ok())?;
+        let s: u64 = c[2].parse().ok()?;
+        Some(Duration::from_secs(m * 60 + s))
+    } else if let Some(c) = minutes.captures(resp) {
+        let m: u64 = match &c[1] {
+            "one" => 1,
+            m => m.parse().ok()?,
+        };
+        Some(Duration::from_secs(m * 60))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// trimmed copies of the pages adventofcode.com sends back after an answer is submitted
+    const CORRECT: &str = include_str!("../fixtures/correct.html");
+    const INCORRECT: &str = include_str!("../fixtures/incorrect.html");
+    const INCORRECT_REPEATED: &str = include_str!("../fixtures/incorrect_repeated.html");
+    const TOO_RECENT: &str = include_str!("../fixtures/too_recent.html");
+    const WRONG_LEVEL: &str = include_str!("../fixtures/wrong_level.html");
+
+    /// trimmed copies of a puzzle page before and after each part is solved
+    const PUZZLE_UNSOLVED: &str = include_str!("../fixtures/puzzle_unsolved.html");
+    const PUZZLE_PART_ONE: &str = include_str!("../fixtures/puzzle_part_one.html");
+    const PUZZLE_COMPLETE: &str = include_str!("../fixtures/puzzle_complete.html");
+
+    /// the verdict and wait parsed from a response page
+    fn parse(page: &str) -> (Verdict, Option<u64>) {
+        let submission = parse_response(page).unwrap();
+        (
+            Verdict::from_message(&submission.message),
+            submission.wait.map(|wait| wait.as_secs()),
+        )
+    }
+
+    #[test]
+    fn verdicts() {
+        assert_eq!(parse(CORRECT), (Verdict::Correct, None));
+        assert_eq!(parse(INCORRECT), (Verdict::Incorrect, Some(60)));
+        assert_eq!(parse(INCORRECT_REPEATED), (Verdict::Incorrect, Some(300)));
+        assert_eq!(parse(TOO_RECENT), (Verdict::TooRecent, Some(123)));
+        assert_eq!(parse(WRONG_LEVEL), (Verdict::WrongLevel, None));
+    }
+
+    #[test]
+    fn messages() {
+        let message = |page| parse_response(page).unwrap().message;
+        assert_eq!(
+            message(INCORRECT),
+            "That's not the right answer; your answer is too low."
+        );
+        assert_eq!(
+            message(TOO_RECENT),
+            "You gave an answer too recently; you have to wait after submitting an answer before trying again."
+        );
+        assert_eq!(
+            message(WRONG_LEVEL),
+            "You don't seem to be solving the right level."
+        );
+        assert!(message(CORRECT).starts_with("That's the right answer!"));
+    }
+
+    #[test]
+    fn unknown_and_missing_messages() {
+        let page = "<main>\n<article>\n<p>Something new happened.</p></article>\n</main>";
+        let submission = parse_response(page).unwrap();
+        assert_eq!(submission.message, "Something new happened.");
+        assert_eq!(Verdict::from_message(&submission.message), Verdict::Unknown);
+
+        let page = "<main><p>No article here.</p></main>";
+        assert!(matches!(
+            parse_response(page),
+            Err(RequestError::MissingApproval(p)) if p == page
+        ));
+    }
+
+    #[test]
+    fn waits() {
+        let wait = |text| parse_wait(text).map(|wait| wait.as_secs());
+        assert_eq!(wait("You have 2m 3s left to wait."), Some(123));
+        assert_eq!(wait("You have 1m 23s left to wait."), Some(83));
+        assert_eq!(wait("You have 9s left to wait."), Some(9));
+        assert_eq!(
+            wait("Please wait one minute before trying again."),
+            Some(60)
+        );
+        assert_eq!(
+            wait("please wait 10 minutes before trying again."),
+            Some(600)
+        );
+        assert_eq!(wait("That's the right answer!"), None);
+    }
+
+    #[test]
+    fn puzzle_answers() {
+        assert_eq!(parse_puzzle_answers(PUZZLE_UNSOLVED), Vec::<String>::new());
+        assert_eq!(parse_puzzle_answers(PUZZLE_PART_ONE), vec!["4714701"]);
+        assert_eq!(
+            parse_puzzle_answers(PUZZLE_COMPLETE),
+            vec!["4714701", "5121"]
+        );
+        // answers are trimmed
+        let page = "<p>Your puzzle answer was <code> ABC </code>.</p>";
+        assert_eq!(parse_puzzle_answers(page), vec!["ABC"]);
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{decode, Mode};
+
+/// format a parameter: [n] for position mode, n for immediate mode and [rb+n] for relative mode
+fn format_param(raw: i64, mode: Mode) -> String {
+    match mode {
+        Mode::Position => format!("[{raw}]"),
+        Mode::Immediate => raw.to_string(),
+        Mode::Relative if raw < 0 => format!("[rb{raw}]"),
+        Mode::Relative => format!("[rb+{raw}]"),
+    }
+}
+
+/// turn a program into a human readable listing, one instruction per line with its address
+/// anything that doesn't decode as an instruction (usually data after the code) is listed as `data`
+pub fn disassemble(program: &[i64]) -> String {
+    let mut listing = String::new();
+    let mut address = 0;
+    while address < program.len() {
+        let decoded = decode(program[address], address)
+            .ok()
+            .filter(|(opcode, _)| address + opcode.params() < program.len());
+
+        let line = match decoded {
+            Some((opcode, modes)) => {
+                let params: Vec<String> = (0..opcode.params())
+                    .map(|i| format_param(program[address + 1 + i], modes[i]))
+                    .collect();
+                let line = format!("{address:>5}: {} {}", opcode.name(), params.join(" "));
+                address += 1 + opcode.params();
+                line
+            }
+            None => {
+                let line = format!("{address:>5}: data {}", program[address]);
+                address += 1;
+                line
+            }
+        };
+        listing.push_str(line.trim_end());
+        listing.push('\n');
+    }
+    listing
+}
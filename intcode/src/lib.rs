@@ -0,0 +1,439 @@
+use std::collections::VecDeque;
+
+extern crate thiserror;
+use thiserror::Error;
+
+mod disassemble;
+pub use disassemble::disassemble;
+
+/// an error encountered while loading or running an intcode program
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum IntcodeError {
+    #[error("unable to parse program: {0:?} is not an integer")]
+    ParseError(String),
+    #[error("unknown opcode {0} at address {1}")]
+    UnknownOpcode(i64, usize),
+    #[error("unknown parameter mode {0} at address {1}")]
+    UnknownMode(i64, usize),
+    #[error("attempted to access negative address {0} from the instruction at address {1}")]
+    NegativeAddress(i64, usize),
+    #[error("attempted to write to an immediate mode parameter at address {0}")]
+    ImmediateWrite(usize),
+}
+
+/// the instructions an intcode machine understands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Opcode {
+    Add,
+    Multiply,
+    Input,
+    Output,
+    JumpIfTrue,
+    JumpIfFalse,
+    LessThan,
+    Equals,
+    AdjustRelativeBase,
+    Halt,
+}
+
+impl Opcode {
+    fn from_i64(n: i64) -> Option<Opcode> {
+        match n {
+            1 => Some(Opcode::Add),
+            2 => Some(Opcode::Multiply),
+            3 => Some(Opcode::Input),
+            4 => Some(Opcode::Output),
+            5 => Some(Opcode::JumpIfTrue),
+            6 => Some(Opcode::JumpIfFalse),
+            7 => Some(Opcode::LessThan),
+            8 => Some(Opcode::Equals),
+            9 => Some(Opcode::AdjustRelativeBase),
+            99 => Some(Opcode::Halt),
+            _ => None,
+        }
+    }
+
+    /// the number of parameters following the instruction
+    fn params(self) -> usize {
+        match self {
+            Opcode::Add | Opcode::Multiply | Opcode::LessThan | Opcode::Equals => 3,
+            Opcode::JumpIfTrue | Opcode::JumpIfFalse => 2,
+            Opcode::Input | Opcode::Output | Opcode::AdjustRelativeBase => 1,
+            Opcode::Halt => 0,
+        }
+    }
+
+    /// the mnemonic used by the disassembler
+    fn name(self) -> &'static str {
+        match self {
+            Opcode::Add => "add",
+            Opcode::Multiply => "mul",
+            Opcode::Input => "in",
+            Opcode::Output => "out",
+            Opcode::JumpIfTrue => "jnz",
+            Opcode::JumpIfFalse => "jz",
+            Opcode::LessThan => "lt",
+            Opcode::Equals => "eq",
+            Opcode::AdjustRelativeBase => "arb",
+            Opcode::Halt => "halt",
+        }
+    }
+}
+
+/// how an instruction's parameter is interpreted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Position,
+    Immediate,
+    Relative,
+}
+
+impl Mode {
+    fn from_i64(n: i64) -> Option<Mode> {
+        match n {
+            0 => Some(Mode::Position),
+            1 => Some(Mode::Immediate),
+            2 => Some(Mode::Relative),
+            _ => None,
+        }
+    }
+}
+
+/// split an instruction into its opcode and the modes of its parameters
+fn decode(instruction: i64, address: usize) -> Result<(Opcode, [Mode; 3]), IntcodeError> {
+    let opcode = Opcode::from_i64(instruction % 100)
+        .ok_or(IntcodeError::UnknownOpcode(instruction, address))?;
+    let mut modes = [Mode::Position; 3];
+    let mut digits = instruction / 100;
+    for mode in modes.iter_mut() {
+        *mode =
+            Mode::from_i64(digits % 10).ok_or(IntcodeError::UnknownMode(instruction, address))?;
+        digits /= 10;
+    }
+    Ok((opcode, modes))
+}
+
+/// why the machine stopped running
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    /// the program produced an output (only returned by `run_until_output`)
+    Output(i64),
+    /// the program wants input but the input queue is empty, push some with `input` and run it again
+    AwaitingInput,
+    /// the program has finished
+    Halted,
+}
+
+/// parse a program in the comma separated format the puzzle inputs use
+pub fn parse_program(input: &str) -> Result<Vec<i64>, IntcodeError> {
+    input
+        .trim()
+        .split(',')
+        .map(|n| {
+            n.trim()
+                .parse()
+                .map_err(|_| IntcodeError::ParseError(n.to_string()))
+        })
+        .collect()
+}
+
+/// an intcode computer with its memory, instruction pointer, relative base and input/output queues
+/// cloning a machine copies its entire state, e.g. to explore several choices from the same point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Machine {
+    memory: Vec<i64>,
+    ip: usize,
+    relative_base: i64,
+    inputs: VecDeque<i64>,
+    outputs: VecDeque<i64>,
+    halted: bool,
+}
+
+impl Machine {
+    /// make a machine ready to run the program from the start
+    pub fn new(program: Vec<i64>) -> Self {
+        Machine {
+            memory: program,
+            ip: 0,
+            relative_base: 0,
+            inputs: VecDeque::new(),
+            outputs: VecDeque::new(),
+            halted: false,
+        }
+    }
+
+    /// make a machine from a program in the comma separated format the puzzle inputs use
+    /// e.g. the text saved by `aocfetch -y 2019 -d 9 -o input.txt`
+    pub fn parse(input: &str) -> Result<Self, IntcodeError> {
+        Ok(Machine::new(parse_program(input)?))
+    }
+
+    /// the machine's memory, which is only as long as the highest address written so far
+    pub fn memory(&self) -> &[i64] {
+        &self.memory
+    }
+
+    /// read memory directly (addresses past the end of the program are 0)
+    pub fn read(&self, address: usize) -> i64 {
+        self.memory.get(address).copied().unwrap_or(0)
+    }
+
+    /// write memory directly, e.g. to set the noun and verb in 2019 day 2
+    pub fn write(&mut self, address: usize, value: i64) {
+        if address >= self.memory.len() {
+            self.memory.resize(address + 1, 0);
+        }
+        self.memory[address] = value;
+    }
+
+    pub fn is_halted(&self) -> bool {
+        self.halted
+    }
+
+    /// add a value to the end of the input queue
+    pub fn input(&mut self, value: i64) {
+        self.inputs.push_back(value);
+    }
+
+    /// add several values to the end of the input queue
+    pub fn inputs(&mut self, values: impl IntoIterator<Item = i64>) {
+        self.inputs.extend(values);
+    }
+
+    /// add ascii text to the end of the input queue, for the text adventure style days
+    pub fn input_ascii(&mut self, text: &str) {
+        self.inputs.extend(text.bytes().map(i64::from));
+    }
+
+    /// take the oldest value from the output queue
+    pub fn output(&mut self) -> Option<i64> {
+        self.outputs.pop_front()
+    }
+
+    /// take every value from the output queue
+    pub fn outputs(&mut self) -> Vec<i64> {
+        self.outputs.drain(..).collect()
+    }
+
+    /// run until the program halts or needs input that isn't in the queue yet
+    /// outputs are collected in the output queue
+    pub fn run(&mut self) -> Result<State, IntcodeError> {
+        loop {
+            match self.step()? {
+                Some(State::Output(value)) => self.outputs.push_back(value),
+                Some(state) => return Ok(state),
+                None => (),
+            }
+        }
+    }
+
+    /// run until the program outputs a value, halts, or needs input that isn't in the queue yet
+    /// the output is returned rather than added to the output queue, which is handy for chaining
+    /// several machines together like the amplifiers in 2019 day 7
+    pub fn run_until_output(&mut self) -> Result<State, IntcodeError> {
+        loop {
+            if let Some(state) = self.step()? {
+                return Ok(state);
+            }
+        }
+    }
+
+    /// the address a parameter refers to
+    fn address(&self, param: usize, mode: Mode) -> Result<usize, IntcodeError> {
+        let raw = self.read(self.ip + 1 + param);
+        let address = match mode {
+            Mode::Position => raw,
+            Mode::Relative => self.relative_base + raw,
+            Mode::Immediate => return Err(IntcodeError::ImmediateWrite(self.ip)),
+        };
+        usize::try_from(address).map_err(|_| IntcodeError::NegativeAddress(address, self.ip))
+    }
+
+    /// the value of a parameter
+    fn get(&self, param: usize, mode: Mode) -> Result<i64, IntcodeError> {
+        match mode {
+            Mode::Immediate => Ok(self.read(self.ip + 1 + param)),
+            _ => Ok(self.read(self.address(param, mode)?)),
+        }
+    }
+
+    /// write to the address a parameter refers to
+    fn set(&mut self, param: usize, mode: Mode, value: i64) -> Result<(), IntcodeError> {
+        let address = self.address(param, mode)?;
+        self.write(address, value);
+        Ok(())
+    }
+
+    /// execute a single instruction, returning the state if it's one the caller needs to know about
+    fn step(&mut self) -> Result<Option<State>, IntcodeError> {
+        if self.halted {
+            return Ok(Some(State::Halted));
+        }
+
+        let (opcode, modes) = decode(self.read(self.ip), self.ip)?;
+        let mut next = self.ip + 1 + opcode.params();
+        let mut state = None;
+        match opcode {
+            Opcode::Add => {
+                let value = self.get(0, modes[0])? + self.get(1, modes[1])?;
+                self.set(2, modes[2], value)?;
+            }
+            Opcode::Multiply => {
+                let value = self.get(0, modes[0])? * self.get(1, modes[1])?;
+                self.set(2, modes[2], value)?;
+            }
+            Opcode::Input => match self.inputs.pop_front() {
+                Some(value) => self.set(0, modes[0], value)?,
+                // pause without moving so the instruction runs again once there's input
+                None => return Ok(Some(State::AwaitingInput)),
+            },
+            Opcode::Output => state = Some(State::Output(self.get(0, modes[0])?)),
+            Opcode::JumpIfTrue => {
+                if self.get(0, modes[0])? != 0 {
+                    next = self.jump_target(modes[1])?;
+                }
+            }
+            Opcode::JumpIfFalse => {
+                if self.get(0, modes[0])? == 0 {
+                    next = self.jump_target(modes[1])?;
+                }
+            }
+            Opcode::LessThan => {
+                let value = (self.get(0, modes[0])? < self.get(1, modes[1])?) as i64;
+                self.set(2, modes[2], value)?;
+            }
+            Opcode::Equals => {
+                let value = (self.get(0, modes[0])? == self.get(1, modes[1])?) as i64;
+                self.set(2, modes[2], value)?;
+            }
+            Opcode::AdjustRelativeBase => self.relative_base += self.get(0, modes[0])?,
+            Opcode::Halt => {
+                self.halted = true;
+                return Ok(Some(State::Halted));
+            }
+        }
+
+        self.ip = next;
+        Ok(state)
+    }
+
+    /// the address a jump instruction's second parameter points to
+    fn jump_target(&self, mode: Mode) -> Result<usize, IntcodeError> {
+        let target = self.get(1, mode)?;
+        usize::try_from(target).map_err(|_| IntcodeError::NegativeAddress(target, self.ip))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// run the program to completion with the inputs, returning everything it output
+    fn run_with(program: &str, inputs: &[i64]) -> Vec<i64> {
+        let mut machine = Machine::parse(program).unwrap();
+        machine.inputs(inputs.iter().copied());
+        assert_eq!(machine.run(), Ok(State::Halted));
+        machine.outputs()
+    }
+
+    #[test]
+    fn day_2() {
+        let mut machine = Machine::parse("1,9,10,3,2,3,11,0,99,30,40,50\n").unwrap();
+        assert_eq!(machine.run(), Ok(State::Halted));
+        assert_eq!(machine.read(0), 3500);
+        assert_eq!(
+            machine.memory(),
+            &[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]
+        );
+        assert!(machine.is_halted());
+        assert_eq!(machine.run(), Ok(State::Halted));
+    }
+
+    #[test]
+    fn day_5_compare_and_jump() {
+        let program = "3,21,1008,21,8,20,1005,20,22,107,8,21,20,1006,20,31,\
+            1106,0,36,98,0,0,1002,21,125,20,4,20,1105,1,46,104,\
+            999,1105,1,46,1101,1000,1,20,4,20,1105,1,46,98,99";
+        assert_eq!(run_with(program, &[7]), vec![999]);
+        assert_eq!(run_with(program, &[8]), vec![1000]);
+        assert_eq!(run_with(program, &[9]), vec![1001]);
+    }
+
+    #[test]
+    fn day_9_quine() {
+        let program = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+        assert_eq!(run_with(program, &[]), parse_program(program).unwrap());
+    }
+
+    #[test]
+    fn day_9_large_numbers() {
+        assert_eq!(
+            run_with("1102,34915192,34915192,7,4,7,99,0", &[]),
+            vec![1_219_070_632_396_864]
+        );
+        assert_eq!(
+            run_with("104,1125899906842624,99", &[]),
+            vec![1_125_899_906_842_624]
+        );
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        // add the two inputs and output the sum
+        let mut machine = Machine::parse("3,11,3,12,1,11,12,13,4,13,99").unwrap();
+        assert_eq!(machine.run(), Ok(State::AwaitingInput));
+        machine.input(40);
+        assert_eq!(machine.run(), Ok(State::AwaitingInput));
+        assert_eq!(machine.run(), Ok(State::AwaitingInput));
+        let paused = machine.clone();
+        machine.input(2);
+        assert_eq!(machine.run_until_output(), Ok(State::Output(42)));
+        assert_eq!(machine.run(), Ok(State::Halted));
+        assert_eq!(machine.output(), None);
+
+        // the clone carries on from where it was paused
+        let mut other = paused;
+        other.input(-40);
+        assert_eq!(other.run(), Ok(State::Halted));
+        assert_eq!(other.outputs(), vec![0]);
+    }
+
+    #[test]
+    fn ascii_input() {
+        // echo three characters
+        let program = "3,0,4,0,3,0,4,0,3,0,4,0,99";
+        let mut machine = Machine::parse(program).unwrap();
+        machine.input_ascii("hi\n");
+        assert_eq!(machine.run(), Ok(State::Halted));
+        assert_eq!(machine.outputs(), vec![104, 105, 10]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut machine = Machine::parse("11101,1,1,0,99").unwrap();
+        assert_eq!(machine.run(), Err(IntcodeError::ImmediateWrite(0)));
+        let mut machine = Machine::parse("1,0,0,0,42").unwrap();
+        assert_eq!(machine.run(), Err(IntcodeError::UnknownOpcode(42, 4)));
+        let mut machine = Machine::parse("301,0,0,0").unwrap();
+        assert_eq!(machine.run(), Err(IntcodeError::UnknownMode(301, 0)));
+        let mut machine = Machine::parse("109,-5,204,0,99").unwrap();
+        assert_eq!(machine.run(), Err(IntcodeError::NegativeAddress(-5, 2)));
+        assert_eq!(
+            parse_program("1,2,x"),
+            Err(IntcodeError::ParseError("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn disassembly() {
+        let listing = disassemble(&parse_program("1002,4,3,4,33").unwrap());
+        assert_eq!(listing, "    0: mul [4] 3 [4]\n    4: data 33\n");
+        let listing = disassemble(&parse_program("109,19,204,-34,1005,1,0,99,1").unwrap());
+        assert_eq!(
+            listing,
+            "    0: arb 19\n    2: out [rb-34]\n    4: jnz [1] 0\n    7: halt\n    8: data 1\n"
+        );
+        // an instruction cut off by the end of the program is data too
+        assert_eq!(disassemble(&[1, 2]), "    0: data 1\n    1: data 2\n");
+    }
+}
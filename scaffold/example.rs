@@ -0,0 +1,10 @@
+// aocnew.sh fills in the module name and the expected answer from the puzzle's example
+use scaffold::{parse, process};
+
+#[test]
+fn example() {
+    let input = include_str!("../example.txt").to_string();
+    let answer = process(parse(input).unwrap());
+
+    assert_eq!(answer.to_string(), "EXPECTED_ANSWER");
+}
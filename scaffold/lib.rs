@@ -1,17 +1,27 @@
+use std::fmt::Display;
 use std::fs;
-use std::io;
+use std::io::{self, Write};
 use std::path::PathBuf;
 
 extern crate clap;
 use clap::Parser;
 
 extern crate anyhow;
+use anyhow::anyhow;
+
+extern crate toml;
+
+extern crate aocsub;
 
 #[derive(Parser, Debug)]
 pub struct Args {
     /// path to the input file
     #[arg(short, long)]
     input: Option<PathBuf>,
+
+    /// submit the answer with aocsub after computing it
+    #[arg(short, long)]
+    submit: bool,
 }
 
 enum InputConfig {
@@ -20,6 +30,7 @@ enum InputConfig {
 }
 pub struct Config {
     input: InputConfig,
+    pub submit: bool,
 }
 
 impl Config {
@@ -31,11 +42,14 @@ impl Config {
             InputConfig::Stdin
         };
 
-        Config { input }
+        Config {
+            input,
+            submit: args.submit,
+        }
     }
 }
 
-pub fn run(cfg: Config) -> anyhow::Result<u32> {
+pub fn run(cfg: Config) -> anyhow::Result<impl Display> {
     // figure out where to get our input from and read it into a string
     let input_string = match cfg.input {
         InputConfig::File(path) => fs::read_to_string(path)?,
@@ -52,12 +66,61 @@ pub fn run(cfg: Config) -> anyhow::Result<u32> {
     Ok(result)
 }
 
-fn parse(input: String) -> anyhow::Result<String> {
+/// the project's manifest, where aocnew.sh records the puzzle's year, day and level
+const MANIFEST: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"));
+
+/// ask for confirmation, then submit the answer with aocsub and print the verdict
+pub fn submit(answer: impl Display) -> anyhow::Result<()> {
+    // figure out which puzzle we're solving from [package.metadata.aoc] in Cargo.toml
+    let manifest: toml::Table = MANIFEST.parse()?;
+    let puzzle = manifest
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("aoc"))
+        .ok_or_else(|| anyhow!("Cargo.toml is missing the [package.metadata.aoc] table"))?;
+    let field = |name: &str| {
+        puzzle
+            .get(name)
+            .and_then(|value| value.as_integer())
+            .ok_or_else(|| anyhow!("[package.metadata.aoc] is missing an integer {name}"))
+    };
+    let year = u16::try_from(field("year")?)?;
+    let day = u8::try_from(field("day")?)?;
+    let level = u8::try_from(field("level")?)?;
+    // an optional account = "NAME" submits with a named account instead of the unnamed one
+    let account = puzzle.get("account").and_then(|value| value.as_str());
+    let mut cfg = aocsub::Config::new(year, day, level, answer.to_string());
+    if let Some(account) = account {
+        cfg = cfg.with_account(account).map_err(|e| anyhow!(e))?;
+    }
+
+    match account {
+        Some(account) => print!(
+            "submit {answer} for {year} day {day} level {level} as account {account}? [y/N] "
+        ),
+        None => print!("submit {answer} for {year} day {day} level {level}? [y/N] "),
+    }
+    io::stdout().flush()?;
+    let mut confirmation = String::new();
+    io::stdin().read_line(&mut confirmation)?;
+    if !confirmation.trim().eq_ignore_ascii_case("y") {
+        return Ok(());
+    }
+
+    let verdict = aocsub::submit(cfg)?;
+    println!("{verdict}");
+
+    Ok(())
+}
+
+pub fn parse(input: String) -> anyhow::Result<String> {
     // remember to change the return type
     todo!()
 }
 
-fn process(data: String) -> u32 {
+pub fn process(data: String) -> impl Display {
     // remember to change the param type
-    todo!()
+    // the answer can be anything that implements Display (u64, i64, u128, String, etc.)
+    let answer: u64 = todo!();
+    answer
 }
@@ -3,10 +3,22 @@ use scaffold::Config;
 use std::process;
 fn main() {
     // remember to change the module name!
-    if let Err(e) = scaffold::run(Config::make()) {
-        eprintln!("{}", e);
-        process::exit(1);
-    } else {
-        process::exit(0);
+    let cfg = Config::make();
+    let submit = cfg.submit;
+    match scaffold::run(cfg) {
+        Ok(answer) => {
+            println!("{}", answer);
+            if submit {
+                if let Err(e) = scaffold::submit(answer) {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                }
+            }
+            process::exit(0);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
     }
 }